@@ -1,16 +1,40 @@
 use std::collections::HashMap;
 
-use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::{prelude::*, JsCast};
 
 use crate::error_to_js;
 
-use super::{session::Session, OlmMessage};
+use super::{session::Session, types::Curve25519PublicKey, OlmMessage};
 
 #[wasm_bindgen]
 pub struct Account {
     inner: vodozemac::olm::Account,
 }
 
+#[derive(Serialize)]
+struct IdentityKeys {
+    ed25519: String,
+    curve25519: String,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const IDENTITY_KEYS: &str = r#"
+export interface IdentityKeys {
+    ed25519: string;
+    curve25519: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "IdentityKeys")]
+    pub type IdentityKeysObject;
+
+    #[wasm_bindgen(typescript_type = "Map<string, string>")]
+    pub type KeyMap;
+}
+
 #[wasm_bindgen]
 pub struct InboundCreationResult {
     session: Session,
@@ -51,7 +75,9 @@ impl Account {
     }
 
     pub fn from_pickle(pickle: &str, pickle_key: &[u8]) -> Result<Account, JsValue> {
-        let pickle_key: &[u8; 32] = pickle_key.try_into().map_err(error_to_js)?;
+        let pickle_key: &[u8; 32] = pickle_key
+            .try_into()
+            .map_err(|_| JsError::new("Invalid pickle key length, expected 32 bytes"))?;
 
         let pickle = vodozemac::olm::AccountPickle::from_encrypted(pickle, pickle_key)
             .map_err(error_to_js)?;
@@ -76,6 +102,17 @@ impl Account {
         Ok(self.inner.pickle().encrypt(pickle_key))
     }
 
+    #[wasm_bindgen(method, getter)]
+    pub fn identity_keys(&self) -> Result<IdentityKeysObject, JsValue> {
+        let identity_keys = self.inner.identity_keys();
+
+        Ok(serde_wasm_bindgen::to_value(&IdentityKeys {
+            ed25519: identity_keys.ed25519.to_base64(),
+            curve25519: identity_keys.curve25519.to_base64(),
+        })?
+        .unchecked_into())
+    }
+
     #[wasm_bindgen(method, getter)]
     pub fn ed25519_key(&self) -> String {
         self.inner.ed25519_key().to_base64()
@@ -90,13 +127,25 @@ impl Account {
         self.inner.sign(message).to_base64()
     }
 
+    pub fn verify(ed25519_key: &str, message: &str, signature: &str) -> Result<(), JsValue> {
+        let key = vodozemac::Ed25519PublicKey::from_base64(ed25519_key).map_err(error_to_js)?;
+        let signature = vodozemac::Ed25519Signature::from_base64(signature).map_err(error_to_js)?;
+
+        key.verify(message.as_bytes(), &signature)
+            .map_err(error_to_js)?;
+
+        Ok(())
+    }
+
+    /// The maximum number of unpublished one-time keys the account holds,
+    /// topping up to it takes a single `generate_one_time_keys()` call.
     #[wasm_bindgen(method, getter)]
     pub fn max_number_of_one_time_keys(&self) -> usize {
         self.inner.max_number_of_one_time_keys()
     }
 
     #[wasm_bindgen(method, getter)]
-    pub fn one_time_keys(&self) -> Result<JsValue, JsValue> {
+    pub fn one_time_keys(&self) -> Result<KeyMap, JsValue> {
         let keys: HashMap<_, _> = self
             .inner
             .one_time_keys()
@@ -104,15 +153,34 @@ impl Account {
             .map(|(k, v)| (k.to_base64(), v.to_base64()))
             .collect();
 
-        Ok(serde_wasm_bindgen::to_value(&keys)?)
+        Ok(serde_wasm_bindgen::to_value(&keys)?.unchecked_into())
     }
 
-    pub fn generate_one_time_keys(&mut self, count: usize) {
-        self.inner.generate_one_time_keys(count)
+    /// Generate `count` new one-time keys.
+    ///
+    /// Only `max_number_of_one_time_keys` unpublished keys fit into the
+    /// account, so this throws if the new keys together with the ones that
+    /// are still waiting to be uploaded wouldn't fit. Upload the keys and call
+    /// `mark_keys_as_published()` to make room for more.
+    pub fn generate_one_time_keys(&mut self, count: usize) -> Result<(), JsValue> {
+        let max = self.inner.max_number_of_one_time_keys();
+        let unpublished = self.inner.one_time_keys().len();
+
+        if count > max.saturating_sub(unpublished) {
+            Err(JsError::new(&format!(
+                "Generating {count} one-time keys would leave more than {max} unpublished keys, \
+                 {unpublished} keys are still waiting to be published"
+            ))
+            .into())
+        } else {
+            self.inner.generate_one_time_keys(count);
+
+            Ok(())
+        }
     }
 
     #[wasm_bindgen(method, getter)]
-    pub fn fallback_key(&self) -> Result<JsValue, JsValue> {
+    pub fn fallback_key(&self) -> Result<KeyMap, JsValue> {
         let keys: HashMap<String, String> = self
             .inner
             .fallback_key()
@@ -120,7 +188,7 @@ impl Account {
             .map(|(k, v)| (k.to_base64(), v.to_base64()))
             .collect();
 
-        Ok(serde_wasm_bindgen::to_value(&keys)?)
+        Ok(serde_wasm_bindgen::to_value(&keys)?.unchecked_into())
     }
 
     pub fn generate_fallback_key(&mut self) {
@@ -147,6 +215,18 @@ impl Account {
         Ok(Session { inner: session })
     }
 
+    pub fn create_outbound_session_with_keys(
+        &self,
+        identity_key: &Curve25519PublicKey,
+        one_time_key: &Curve25519PublicKey,
+    ) -> Session {
+        let session = self
+            .inner
+            .create_outbound_session(identity_key.inner, one_time_key.inner);
+
+        Session { inner: session }
+    }
+
     pub fn create_inbound_session(
         &mut self,
         identity_key: &str,
@@ -155,6 +235,24 @@ impl Account {
         let identity_key =
             vodozemac::Curve25519PublicKey::from_base64(identity_key).map_err(error_to_js)?;
 
+        self.create_inbound_session_helper(identity_key, message)
+    }
+
+    pub fn create_inbound_session_with_key(
+        &mut self,
+        identity_key: &Curve25519PublicKey,
+        message: &OlmMessage,
+    ) -> Result<InboundCreationResult, JsValue> {
+        self.create_inbound_session_helper(identity_key.inner, message)
+    }
+}
+
+impl Account {
+    fn create_inbound_session_helper(
+        &mut self,
+        identity_key: vodozemac::Curve25519PublicKey,
+        message: &OlmMessage,
+    ) -> Result<InboundCreationResult, JsValue> {
         let message =
             vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)
                 .map_err(error_to_js)?;
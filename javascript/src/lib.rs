@@ -4,10 +4,13 @@ mod account;
 mod group_sessions;
 mod sas;
 mod session;
+mod types;
 
 pub use account::Account;
+pub use group_sessions::{DecryptedMessage, GroupSession, InboundGroupSession};
 pub use sas::{EstablishedSas, Sas, SasBytes};
 pub use session::Session;
+pub use types::{Curve25519PublicKey, Ed25519PublicKey};
 
 use wasm_bindgen::prelude::*;
 
@@ -30,4 +33,15 @@ impl OlmMessage {
             message_type,
         }
     }
+
+    pub fn from_parts(message_type: usize, ciphertext: &str) -> Result<OlmMessage, JsValue> {
+        let message = vodozemac::olm::OlmMessage::from_parts(message_type, ciphertext)
+            .map_err(error_to_js)?;
+        let (message_type, ciphertext) = message.to_parts();
+
+        Ok(Self {
+            ciphertext,
+            message_type,
+        })
+    }
 }
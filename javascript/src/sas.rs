@@ -4,30 +4,38 @@ use crate::error_to_js;
 
 #[wasm_bindgen]
 pub struct Sas {
-    inner: vodozemac::sas::Sas,
+    inner: Option<vodozemac::sas::Sas>,
+    public_key: String,
 }
 
 #[wasm_bindgen]
 impl Sas {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
+        let sas = vodozemac::sas::Sas::new();
+        let public_key = sas.public_key().to_base64();
+
         Self {
-            inner: vodozemac::sas::Sas::new(),
+            inner: Some(sas),
+            public_key,
         }
     }
 
     #[wasm_bindgen(getter)]
     pub fn public_key(&self) -> String {
-        self.inner.public_key().to_base64()
+        self.public_key.clone()
     }
 
-    pub fn diffie_hellman(self, key: &str) -> Result<EstablishedSas, JsValue> {
-        let sas = self
-            .inner
-            .diffie_hellman_with_raw(key)
-            .map_err(error_to_js)?;
+    pub fn diffie_hellman(&mut self, key: &str) -> Result<EstablishedSas, JsValue> {
+        let key = vodozemac::Curve25519PublicKey::from_base64(key).map_err(error_to_js)?;
 
-        Ok(EstablishedSas { inner: sas })
+        if let Some(sas) = self.inner.take() {
+            let sas = sas.diffie_hellman(key).map_err(error_to_js)?;
+
+            Ok(EstablishedSas { inner: sas })
+        } else {
+            Err(JsError::new("The Sas object has already been used once.").into())
+        }
     }
 }
 
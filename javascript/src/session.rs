@@ -43,6 +43,11 @@ impl Session {
         self.inner.session_id()
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn has_received_message(&self) -> bool {
+        self.inner.has_received_message()
+    }
+
     pub fn session_matches(&self, message: &OlmMessage) -> bool {
         let message =
             vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext);
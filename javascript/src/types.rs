@@ -0,0 +1,49 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error_to_js;
+
+#[wasm_bindgen]
+pub struct Curve25519PublicKey {
+    pub(crate) inner: vodozemac::Curve25519PublicKey,
+}
+
+#[wasm_bindgen]
+impl Curve25519PublicKey {
+    pub fn from_base64(key: &str) -> Result<Curve25519PublicKey, JsValue> {
+        Ok(Self {
+            inner: vodozemac::Curve25519PublicKey::from_base64(key).map_err(error_to_js)?,
+        })
+    }
+
+    pub fn to_base64(&self) -> String {
+        self.inner.to_base64()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Ed25519PublicKey {
+    pub(crate) inner: vodozemac::Ed25519PublicKey,
+}
+
+#[wasm_bindgen]
+impl Ed25519PublicKey {
+    pub fn from_base64(key: &str) -> Result<Ed25519PublicKey, JsValue> {
+        Ok(Self {
+            inner: vodozemac::Ed25519PublicKey::from_base64(key).map_err(error_to_js)?,
+        })
+    }
+
+    pub fn to_base64(&self) -> String {
+        self.inner.to_base64()
+    }
+
+    pub fn verify(&self, message: &str, signature: &str) -> Result<(), JsValue> {
+        let signature = vodozemac::Ed25519Signature::from_base64(signature).map_err(error_to_js)?;
+
+        self.inner
+            .verify(message.as_bytes(), &signature)
+            .map_err(error_to_js)?;
+
+        Ok(())
+    }
+}
@@ -9,6 +9,27 @@ describe('Olm Account', function() {
         expect(account.curve25519_key).not.toBe("");
     });
 
+    it('should return both identity keys at once', function() {
+        let account = new Account();
+
+        expect(account.identity_keys).toEqual({
+            ed25519: account.ed25519_key,
+            curve25519: account.curve25519_key,
+        });
+    });
+
+    it('should verify signatures', function() {
+        let account = new Account();
+        const message = "It's a secret to everybody";
+        const signature = account.sign(message);
+
+        Account.verify(account.ed25519_key, message, signature);
+
+        expect(() => Account.verify(account.ed25519_key, "Grumble, Grumble", signature)).toThrow();
+        expect(() => Account.verify(new Account().ed25519_key, message, signature)).toThrow();
+        expect(() => Account.verify(account.ed25519_key, message, "")).toThrow();
+    });
+
     it('should generate one-time keys', function() {
         let account = new Account();
 
@@ -20,6 +41,38 @@ describe('Olm Account', function() {
         expect(account.one_time_keys.size).toBe(0);
     });
 
+    it('should generate the maximum number of one-time keys in one call', function() {
+        let account = new Account();
+        const max = account.max_number_of_one_time_keys;
+
+        account.generate_one_time_keys(max);
+        expect(account.one_time_keys.size).toBe(max);
+    });
+
+    it('should refuse to generate more one-time keys than it can hold', function() {
+        let account = new Account();
+        const max = account.max_number_of_one_time_keys;
+
+        expect(() => account.generate_one_time_keys(max + 1)).toThrow(`more than ${max}`);
+        expect(account.one_time_keys.size).toBe(0);
+    });
+
+    it('should count unpublished one-time keys towards the limit', function() {
+        let account = new Account();
+        const max = account.max_number_of_one_time_keys;
+
+        account.generate_one_time_keys(max - 1);
+        account.generate_one_time_keys(1);
+        expect(account.one_time_keys.size).toBe(max);
+
+        expect(() => account.generate_one_time_keys(1)).toThrow(`more than ${max}`);
+        expect(account.one_time_keys.size).toBe(max);
+
+        account.mark_keys_as_published();
+        account.generate_one_time_keys(1);
+        expect(account.one_time_keys.size).toBe(1);
+    });
+
     it('should generate fallback keys', function() {
         let account = new Account();
 
@@ -45,6 +98,14 @@ describe('Olm Account', function() {
         expect(account.ed25519_key).toEqual(unpickled.ed25519_key);
     });
 
+    it('should reject pickle keys of the wrong length', function() {
+        let account = new Account();
+        let pickled = account.pickle(PICKLE_KEY);
+
+        expect(() => account.pickle("too short")).toThrow("expected 32 bytes");
+        expect(() => Account.from_pickle(pickled, "too short")).toThrow("expected 32 bytes");
+    });
+
     it('should let us unpickle an libolm account', function() {
         const libolm_pickle = "3wpPcPT4xsRYCYF34NcnozxE5bN2E6qwBXQYuoovt/TX//" +
                               "8Dnd8gaKsxN9En/7Hkh5XemuGUo3dXHVTl76G2pjf9ehfr" +
@@ -59,7 +120,23 @@ describe('Olm Account', function() {
         expect(account.ed25519_key).toEqual("MEQCwaTE/gcrHaxwv06WEVy5xDA30FboFzCAtYhzmoc")
     });
 
+    it('should throw an exception if the libolm pickle is not valid', function() {
+        let pickle_key = Buffer.from("It's a secret to everybody");
+
+        expect(() => Account.from_libolm_pickle("", pickle_key)).toThrow(Error);
+    });
+
     it('should throw an exception if the pickle is not valid', function() {
         expect(() => Account.from_pickle("", PICKLE_KEY)).toThrow();
     });
+
+    it('should explain why a key could not be decoded', function() {
+        let alice = new Account();
+        let bob = new Account();
+        bob.generate_one_time_keys(1);
+        let [one_time_key] = bob.one_time_keys.values();
+
+        expect(() => alice.create_outbound_session("AAAA", one_time_key)).toThrow(/32/);
+        expect(() => alice.create_outbound_session(bob.curve25519_key, "AAAA")).toThrow(/32/);
+    });
 });
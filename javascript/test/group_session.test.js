@@ -1,8 +1,13 @@
+const Olm = require('@matrix-org/olm');
 const { GroupSession, InboundGroupSession } = require('../pkg/vodozemac.js');
 
 const PICKLE_KEY = "DEFAULT_PICKLE_KEY_1234567890___";
 
 describe('Megolm Session', function() {
+    beforeAll(async function() {
+        await Olm.init();
+    });
+
     it('should be created successfully', function() {
         const session = new GroupSession();
         const inbound = new InboundGroupSession(session.session_key);
@@ -33,6 +38,21 @@ describe('Megolm Session', function() {
         expect(session.session_id).toEqual(unpickled.session_id);
     });
 
+    it('should keep the message index of a pickled outbound group session', function() {
+        const session = new GroupSession();
+        const inbound = new InboundGroupSession(session.session_key);
+
+        session.encrypt("Test");
+        session.encrypt("Test 2");
+
+        const unpickled = GroupSession.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY);
+        expect(unpickled.message_index).toBe(2);
+
+        const { message_index } = inbound.decrypt(unpickled.encrypt("Test 3"));
+        expect(message_index).toBe(2);
+        expect(unpickled.message_index).toBe(3);
+    });
+
     it('should let us pickle the inbound group session', function() {
         const outbound = new GroupSession();
         const session = new InboundGroupSession(outbound.session_key);
@@ -43,11 +63,65 @@ describe('Megolm Session', function() {
         expect(session.session_id).toEqual(unpickled.session_id);
     });
 
+    it('should keep the first known index of a pickled inbound group session', function() {
+        const outbound = new GroupSession();
+        const early_message = outbound.encrypt("Test");
+        const session = new InboundGroupSession(outbound.session_key);
+
+        const unpickled = InboundGroupSession.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY);
+        expect(unpickled.first_known_index).toBe(1);
+        expect(() => unpickled.decrypt(early_message)).toThrow();
+
+        const { plaintext, message_index } = unpickled.decrypt(outbound.encrypt("Test 2"));
+        expect(plaintext).toEqual("Test 2");
+        expect(message_index).toBe(1);
+    });
+
     it('should throw an exception if unpickling fails', function() {
         expect(() => GroupSession.from_pickle("", PICKLE_KEY)).toThrow();
         expect(() => InboundGroupSession.from_pickle("", PICKLE_KEY)).toThrow();
     });
 
+    it('should throw an exception if the libolm pickle is not valid', function() {
+        const pickle_key = Buffer.from("It's a secret to everybody");
+
+        expect(() => InboundGroupSession.from_libolm_pickle("", pickle_key)).toThrow(Error);
+        expect(() => InboundGroupSession.from_libolm_pickle("not base64!", pickle_key)).toThrow(Error);
+    });
+
+    it('should let us unpickle a libolm inbound group session', function() {
+        const outbound = new Olm.OutboundGroupSession();
+        outbound.create();
+        const early_message = outbound.encrypt("Test 1");
+
+        const libolm_session = new Olm.InboundGroupSession();
+        libolm_session.create(outbound.session_key());
+        const message = outbound.encrypt("Test 2");
+        const libolm_pickle = libolm_session.pickle("It's a secret to everybody");
+
+        const pickle_key = Buffer.from("It's a secret to everybody");
+        const session = InboundGroupSession.from_libolm_pickle(libolm_pickle, pickle_key);
+
+        expect(session.session_id).toEqual(libolm_session.session_id());
+        expect(session.first_known_index).toBe(1);
+        expect(() => session.decrypt(early_message)).toThrow();
+
+        const { plaintext, message_index } = session.decrypt(message);
+        expect(plaintext).toEqual("Test 2");
+        expect(message_index).toBe(1);
+
+        libolm_session.free();
+        outbound.free();
+    });
+
+    it('should throw an exception if the session key is not valid', function() {
+        const outbound = new GroupSession();
+
+        expect(() => new InboundGroupSession("")).toThrow(Error);
+        expect(() => InboundGroupSession.import("")).toThrow(Error);
+        expect(() => InboundGroupSession.import(outbound.session_key)).toThrow(Error);
+    });
+
     it('should throw an exception if decryption fails', function() {
         const outbound = new GroupSession();
         const session = new InboundGroupSession(outbound.session_key);
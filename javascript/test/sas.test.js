@@ -19,6 +19,27 @@ describe('Sas', function() {
         alice = alice.diffie_hellman(bob_public_key);
     });
 
+    it('should throw an exception if the peer key is not valid', function() {
+        const alice = new Sas();
+        const bob = new Sas();
+
+        for (const garbage of ["", "not base64!", "AAAA"]) {
+            expect(() => alice.diffie_hellman(garbage)).toThrow(Error);
+        }
+
+        alice.diffie_hellman(bob.public_key);
+    });
+
+    it('should not allow us to establish a shared secret twice', function() {
+        const alice = new Sas();
+        const bob = new Sas();
+
+        alice.diffie_hellman(bob.public_key);
+
+        expect(alice.public_key).not.toBe("");
+        expect(() => alice.diffie_hellman(bob.public_key)).toThrow("already been used");
+    });
+
     it('should allow us to generate common short auth strings', function() {
         let alice = new Sas();
         let bob = new Sas();
@@ -34,6 +55,23 @@ describe('Sas', function() {
         expect(alice_bytes.decimals).toEqual(bob_bytes.decimals);
     });
 
+    it('should generate seven emoji indices and three decimals', function() {
+        let alice = new Sas();
+        const bob = new Sas();
+
+        alice = alice.diffie_hellman(bob.public_key);
+        const bytes = alice.bytes(EXTRA_INFO);
+
+        expect(bytes.emoji_indices).toHaveLength(7);
+        bytes.emoji_indices.forEach((index) => expect(index).toBeLessThan(64));
+
+        expect(bytes.decimals).toHaveLength(3);
+        bytes.decimals.forEach((decimal) => {
+            expect(decimal).toBeGreaterThanOrEqual(1000);
+            expect(decimal).toBeLessThanOrEqual(9191);
+        });
+    });
+
     it('should allow us to generate a message authentication code', function() {
         let alice = new Sas();
         let bob = new Sas();
@@ -1,3 +1,4 @@
+const Olm = require('@matrix-org/olm');
 const { Account, Session, OlmMessage } = require('../pkg/vodozemac.js');
 
 const PICKLE_KEY = "DEFAULT_PICKLE_KEY_1234567890___";
@@ -15,6 +16,10 @@ function create_session() {
 }
 
 describe('Olm Session', function() {
+    beforeAll(async function() {
+        await Olm.init();
+    });
+
     it('should be created successfully', function() {
         const [_alice, _bob, session] = create_session();
         expect(session.session_id).not.toBe("");
@@ -39,6 +44,74 @@ describe('Olm Session', function() {
         expect(() => Session.from_pickle("", PICKLE_KEY)).toThrow();
     });
 
+    it('should throw an exception if the libolm pickle is not valid', function() {
+        const pickle_key = Buffer.from("It's a secret to everybody");
+
+        expect(() => Session.from_libolm_pickle("", pickle_key)).toThrow(Error);
+        expect(() => Session.from_libolm_pickle("not base64!", pickle_key)).toThrow(Error);
+    });
+
+    it('should let us unpickle a libolm session', function() {
+        const alice = new Olm.Account();
+        alice.create();
+        const { curve25519: alice_key } = JSON.parse(alice.identity_keys());
+
+        const bob = new Account();
+        bob.generate_one_time_keys(1);
+        const [one_time_key] = bob.one_time_keys.values();
+
+        const libolm_session = new Olm.Session();
+        libolm_session.create_outbound(alice, bob.curve25519_key, one_time_key);
+        const { type, body } = libolm_session.encrypt("It's a secret to everybody");
+        const message = OlmMessage.from_parts(type, body);
+        const { session: bob_session } = bob.create_inbound_session(alice_key, message);
+
+        const libolm_pickle = libolm_session.pickle("It's a secret to everybody");
+        const pickle_key = Buffer.from("It's a secret to everybody");
+        const session = Session.from_libolm_pickle(libolm_pickle, pickle_key);
+
+        expect(session.session_id).toEqual(libolm_session.session_id());
+        expect(session.session_id).toEqual(bob_session.session_id);
+        expect(bob_session.decrypt(session.encrypt("Grumble, Grumble"))).toEqual("Grumble, Grumble");
+        expect(session.decrypt(bob_session.encrypt("Hoot, Hoot"))).toEqual("Hoot, Hoot");
+
+        libolm_session.free();
+        alice.free();
+    });
+
+    it('should continue the ratchet after unpickling', function() {
+        const [alice, bob, session] = create_session();
+        const message = session.encrypt("It's a secret to everybody");
+        const { session: bob_session } = bob.create_inbound_session(alice.curve25519_key, message);
+
+        const bob_message = bob_session.encrypt("Grumble, Grumble");
+        const unpickled = Session.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY);
+
+        expect(unpickled.decrypt(bob_message)).toEqual("Grumble, Grumble");
+    });
+
+    it('should throw an exception if the pickle key has the wrong length', function() {
+        const [_alice, _bob, session] = create_session();
+        const pickled = session.pickle(PICKLE_KEY);
+
+        expect(() => session.pickle("too short")).toThrow("expected 32 bytes");
+        expect(() => Session.from_pickle(pickled, "too short")).toThrow("expected 32 bytes");
+    });
+
+    it('should let us construct a message from its parts', function() {
+        const [_alice, _bob, session] = create_session();
+        const encrypted = session.encrypt("It's a secret to everybody");
+        const message = OlmMessage.from_parts(encrypted.message_type, encrypted.ciphertext);
+
+        expect(message.message_type).toEqual(encrypted.message_type);
+        expect(message.ciphertext).toEqual(encrypted.ciphertext);
+    });
+
+    it('should throw an exception if the message parts are not valid', function() {
+        expect(() => OlmMessage.from_parts(2, "")).toThrow();
+        expect(() => OlmMessage.from_parts(0, "not a message")).toThrow();
+    });
+
     it('should let us encrypt and decrypt messages', function() {
         const plaintext = "It's a secret to everybody"
         const [alice, bob, session] = create_session();
@@ -52,12 +125,51 @@ describe('Olm Session', function() {
         expect(session.session_id).toEqual(bob_session.session_id);
     });
 
+    it('should remember if a message was received', function() {
+        const [alice, bob, session] = create_session();
+        const message = session.encrypt("It's a secret to everybody");
+        const { session: bob_session } = bob.create_inbound_session(alice.curve25519_key, message);
+
+        expect(session.has_received_message).toBe(false);
+        expect(bob_session.has_received_message).toBe(true);
+
+        session.decrypt(bob_session.encrypt("Grumble, Grumble"));
+        expect(session.has_received_message).toBe(true);
+    });
+
+    it('should switch to normal messages once a message was received', function() {
+        const [alice, bob, session] = create_session();
+
+        const message = session.encrypt("It's a secret to everybody");
+        expect(message.message_type).toBe(0);
+        expect(message.ciphertext).not.toBe("");
+
+        const { session: bob_session } = bob.create_inbound_session(alice.curve25519_key, message);
+        expect(session.encrypt("Hey! Listen!").message_type).toBe(0);
+
+        session.decrypt(bob_session.encrypt("Grumble, Grumble"));
+
+        const normal_message = session.encrypt("Hey! Listen!");
+        expect(normal_message.message_type).toBe(1);
+        expect(bob_session.decrypt(normal_message)).toEqual("Hey! Listen!");
+    });
+
     it('should throw an exception if the message is not valid', function() {
         const [_alice, _bob, session] = create_session();
         const message = new OlmMessage(0, "x");
         expect(() => session.decrypt(message)).toThrow();
     });
 
+    it('should throw an exception if the message belongs to another session', function() {
+        const [_alice, _bob, session] = create_session();
+        const [alice2, bob2, session2] = create_session();
+
+        let { session: bob_session } = bob2.create_inbound_session(alice2.curve25519_key, session2.encrypt("Test"));
+        const message = bob_session.encrypt("Grumble grumble");
+
+        expect(() => session.decrypt(message)).toThrow();
+    });
+
     it('should let us encrypt and decrypt multiple messages', function() {
         let plaintext = "It's a secret to everybody"
         const [alice, bob, session] = create_session();
@@ -98,6 +210,23 @@ describe('Olm Session', function() {
         expect(() => bob.create_inbound_session(alice.curve25519_key, message)).toThrow();
     });
 
+    it('should throw an error if only the one-time key is malformed', function() {
+        const [alice, bob, _session] = create_session();
+
+        expect(() => alice.create_outbound_session(bob.curve25519_key, "not base64!")).toThrow(Error);
+    });
+
+    it('should throw an error if a normal message is used to create a session', function() {
+        const [alice, bob, session] = create_session();
+        let { session: bob_session } = bob.create_inbound_session(alice.curve25519_key, session.encrypt("Test"));
+
+        const message = bob_session.encrypt("Grumble grumble");
+        expect(message.message_type).toBe(1);
+
+        expect(() => alice.create_inbound_session(bob.curve25519_key, message)).toThrow("pre-key");
+        expect(() => bob.create_inbound_session("x", session.encrypt("Test"))).toThrow(Error);
+    });
+
     it("should tell us if a pre-key message doesn't match a session", function() {
         const plaintext = "It's a secret to everybody"
         const [alice, bob, session] = create_session();
@@ -0,0 +1,65 @@
+const { Account, Curve25519PublicKey, Ed25519PublicKey } = require('../pkg/vodozemac.js');
+
+const MESSAGE = "It's a secret to everybody";
+
+describe('Curve25519PublicKey', function() {
+    it('should be parsed from base64', function() {
+        const account = new Account();
+        const key = Curve25519PublicKey.from_base64(account.curve25519_key);
+
+        expect(key.to_base64()).toEqual(account.curve25519_key);
+    });
+
+    it('should throw an exception if the key is not valid', function() {
+        expect(() => Curve25519PublicKey.from_base64("")).toThrow(Error);
+    });
+
+    it('should be usable to create sessions', function() {
+        const alice = new Account();
+        const bob = new Account();
+        bob.generate_one_time_keys(1);
+        const [one_time_key] = bob.one_time_keys.values();
+
+        const session = alice.create_outbound_session_with_keys(
+            Curve25519PublicKey.from_base64(bob.curve25519_key),
+            Curve25519PublicKey.from_base64(one_time_key),
+        );
+        const message = session.encrypt(MESSAGE);
+        const { plaintext } = bob.create_inbound_session_with_key(
+            Curve25519PublicKey.from_base64(alice.curve25519_key),
+            message,
+        );
+
+        expect(plaintext).toEqual(MESSAGE);
+    });
+});
+
+describe('Ed25519PublicKey', function() {
+    it('should be parsed from base64', function() {
+        const account = new Account();
+        const key = Ed25519PublicKey.from_base64(account.ed25519_key);
+
+        expect(key.to_base64()).toEqual(account.ed25519_key);
+    });
+
+    it('should throw an exception if the key is not valid', function() {
+        expect(() => Ed25519PublicKey.from_base64("")).toThrow(Error);
+    });
+
+    it('should verify signatures', function() {
+        const account = new Account();
+        const key = Ed25519PublicKey.from_base64(account.ed25519_key);
+        const signature = account.sign(MESSAGE);
+
+        key.verify(MESSAGE, signature);
+    });
+
+    it('should throw an exception if the signature does not match', function() {
+        const account = new Account();
+        const key = Ed25519PublicKey.from_base64(account.ed25519_key);
+        const signature = account.sign(MESSAGE);
+
+        expect(() => key.verify("It's a secret to nobody", signature)).toThrow(Error);
+        expect(() => key.verify(MESSAGE, "")).toThrow(Error);
+    });
+});
@@ -0,0 +1,16 @@
+const fs = require('fs');
+const path = require('path');
+
+const definitions = fs.readFileSync(path.join(__dirname, '../pkg/vodozemac.d.ts'), 'utf8');
+
+describe('TypeScript definitions', function() {
+    it('should describe the identity keys object', function() {
+        expect(definitions).toContain('export interface IdentityKeys');
+        expect(definitions).toMatch(/identity_keys: IdentityKeys;/);
+    });
+
+    it('should describe the key maps', function() {
+        expect(definitions).toMatch(/one_time_keys: Map<string, string>;/);
+        expect(definitions).toMatch(/fallback_key: Map<string, string>;/);
+    });
+});
@@ -0,0 +1,72 @@
+use std::{env, fs, path::PathBuf};
+
+use toml::Value;
+
+/// The source of our vodozemac dependency the way Cargo.lock records it,
+/// e.g. `git+https://github.com/matrix-org/vodozemac?rev=...`.
+fn vodozemac_source(manifest: &Value) -> String {
+    let dependency = manifest
+        .get("dependencies")
+        .and_then(|dependencies| dependencies.get("vodozemac"))
+        .expect("vodozemac is missing from the dependencies in Cargo.toml");
+
+    let git = dependency
+        .get("git")
+        .and_then(Value::as_str)
+        .expect("vodozemac is expected to be a git dependency");
+
+    let reference = ["rev", "tag", "branch"]
+        .into_iter()
+        .find_map(|kind| {
+            let value = dependency.get(kind)?.as_str()?;
+
+            Some(format!("?{kind}={value}"))
+        })
+        .unwrap_or_default();
+
+    format!("git+{git}{reference}")
+}
+
+/// Find the commit the source got resolved to, Cargo.lock appends it to the
+/// source after a `#`.
+fn vodozemac_revision(lockfile: &Value, source: &str) -> Option<String> {
+    lockfile
+        .get("package")?
+        .as_array()?
+        .iter()
+        .filter(|package| package.get("name").and_then(Value::as_str) == Some("vodozemac"))
+        .filter_map(|package| package.get("source")?.as_str())
+        .find_map(|package_source| {
+            let (package_source, revision) = package_source.split_once('#')?;
+
+            (package_source == source).then(|| revision.to_owned())
+        })
+}
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let manifest_path = manifest_dir.join("Cargo.toml");
+    let lockfile_path = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists())
+        .expect("Couldn't find the Cargo.lock file");
+
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+    println!("cargo:rerun-if-changed={}", lockfile_path.display());
+
+    let manifest: Value = fs::read_to_string(&manifest_path)
+        .expect("Couldn't read Cargo.toml")
+        .parse()
+        .expect("Couldn't parse Cargo.toml");
+    let lockfile: Value = fs::read_to_string(&lockfile_path)
+        .expect("Couldn't read Cargo.lock")
+        .parse()
+        .expect("Couldn't parse Cargo.lock");
+
+    let source = vodozemac_source(&manifest);
+    let revision = vodozemac_revision(&lockfile, &source)
+        .unwrap_or_else(|| panic!("Couldn't find the resolved revision of {source} in Cargo.lock"));
+
+    println!("cargo:rustc-env=VODOZEMAC_REVISION={revision}");
+}
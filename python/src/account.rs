@@ -1,142 +1,424 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard},
+};
 
-use pyo3::{prelude::*, types::PyType};
+use pyo3::{
+    basic::CompareOp,
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyDict, PyType},
+    AsPyPointer,
+};
+use subtle::ConstantTimeEq;
+use vodozemac::{olm::PreKeyMessage, Curve25519PublicKey};
 
-use crate::error::{KeyError, LibolmPickleError, PickleError, SessionError};
+use crate::{
+    convert_pickle_key,
+    error::{DestroyedError, KeyError, LibolmPickleError, PickleError, SessionError},
+};
 
-use super::{session::Session, OlmMessage};
+use super::{
+    session::Session,
+    types::{hash, AnyCurve25519PublicKey},
+    OlmMessage,
+};
 
 #[pyclass]
 pub struct Account {
-    inner: vodozemac::olm::Account,
+    inner: Mutex<Option<vodozemac::olm::Account>>,
+}
+
+/// A locked account that is known not to be destroyed.
+struct LockedAccount<'a>(MutexGuard<'a, Option<vodozemac::olm::Account>>);
+
+impl Deref for LockedAccount<'_> {
+    type Target = vodozemac::olm::Account;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+            .as_ref()
+            .expect("A locked account is never destroyed")
+    }
+}
+
+impl DerefMut for LockedAccount<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+            .as_mut()
+            .expect("A locked account is never destroyed")
+    }
+}
+
+impl Account {
+    fn lock(&self) -> MutexGuard<'_, Option<vodozemac::olm::Account>> {
+        self.inner.lock().expect("The Account lock was poisoned")
+    }
+
+    /// Lock the account, other threads using it block until the returned
+    /// guard is dropped. Don't hold on to it while the GIL is released and
+    /// reacquired, lock the account inside of `allow_threads()` instead.
+    fn inner(&self) -> Result<LockedAccount<'_>, DestroyedError> {
+        let inner = self.lock();
+
+        if inner.is_some() {
+            Ok(LockedAccount(inner))
+        } else {
+            Err(DestroyedError("Account"))
+        }
+    }
+
+    /// Compare the identity keys of the two accounts, `None` if one of them
+    /// has been destroyed.
+    fn identity_keys_eq(&self, other: &Self) -> Option<bool> {
+        let ours = self.inner().ok()?.identity_keys();
+        let theirs = other.inner().ok()?.identity_keys();
+
+        let ed25519 = ours.ed25519.as_bytes()[..].ct_eq(&theirs.ed25519.as_bytes()[..]);
+        let curve25519 = ours.curve25519.as_bytes()[..].ct_eq(&theirs.curve25519.as_bytes()[..]);
+
+        Some((ed25519 & curve25519).into())
+    }
+}
+
+impl From<vodozemac::olm::Account> for Account {
+    fn from(account: vodozemac::olm::Account) -> Self {
+        Self {
+            inner: Mutex::new(Some(account)),
+        }
+    }
 }
 
 #[pymethods]
 impl Account {
     #[new]
-    fn new() -> Self {
-        Self {
-            inner: vodozemac::olm::Account::new(),
+    fn new(py: Python) -> Self {
+        py.allow_threads(vodozemac::olm::Account::new).into()
+    }
+
+    fn __repr__(&self) -> String {
+        match &*self.lock() {
+            Some(inner) => format!("Account(ed25519_key={})", inner.ed25519_key().to_base64()),
+            None => "Account(destroyed)".to_owned(),
         }
     }
 
+    /// Accounts compare equal if they share the same identity keys, the rest
+    /// of the account state, e.g. the one-time keys, isn't compared.
+    ///
+    /// Destroyed accounts no longer have any keys, they fall back to Python's
+    /// identity comparison.
+    fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+        match (op, self.identity_keys_eq(other)) {
+            (CompareOp::Eq, Some(eq)) => eq.into_py(py),
+            (CompareOp::Ne, Some(eq)) => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    /// Hashes the identity keys, so accounts that compare equal end up with
+    /// the same hash. Destroyed accounts are hashed by their identity.
+    fn __hash__(slf: PyRef<Self>) -> u64 {
+        match slf.inner() {
+            Ok(inner) => {
+                let identity_keys = inner.identity_keys();
+
+                hash(
+                    &[
+                        identity_keys.ed25519.as_bytes().as_slice(),
+                        identity_keys.curve25519.as_bytes().as_slice(),
+                    ]
+                    .concat(),
+                )
+            }
+            Err(_) => hash(&(slf.as_ptr() as usize).to_ne_bytes()),
+        }
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.destroy();
+
+        false
+    }
+
+    /// Drop the key material of the account, vodozemac zeroizes it on drop.
+    /// Any further use of the account raises a `DestroyedException`.
+    ///
+    /// Calling this is optional, the key material is dropped as well once the
+    /// account gets garbage collected.
+    fn destroy(&self) {
+        *self.lock() = None;
+    }
+
     #[classmethod]
     fn from_pickle(_cls: &PyType, pickle: &str, pickle_key: &[u8]) -> Result<Self, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+        let pickle_key = convert_pickle_key(pickle_key)?;
 
-        let pickle = vodozemac::olm::AccountPickle::from_encrypted(pickle, pickle_key)?;
+        let pickle = vodozemac::olm::AccountPickle::from_encrypted(pickle, &pickle_key)?;
 
-        let inner = vodozemac::olm::Account::from_pickle(pickle);
-
-        Ok(Self { inner })
+        Ok(vodozemac::olm::Account::from_pickle(pickle).into())
     }
 
+    /// Import an account from a libolm pickle. This is a one-way migration
+    /// path, vodozemac can't produce libolm pickles so there's no matching
+    /// export, use `pickle()` to persist the account afterwards.
     #[classmethod]
     fn from_libolm_pickle(
         _cls: &PyType,
         pickle: &str,
         pickle_key: &[u8],
     ) -> Result<Self, LibolmPickleError> {
-        let inner = vodozemac::olm::Account::from_libolm_pickle(pickle, pickle_key)?;
+        Ok(vodozemac::olm::Account::from_libolm_pickle(pickle, pickle_key)?.into())
+    }
+
+    fn pickle(&self, pickle_key: &[u8]) -> PyResult<String> {
+        let pickle_key = convert_pickle_key(pickle_key)?;
 
-        Ok(Self { inner })
+        Ok(self.inner()?.pickle().encrypt(&pickle_key))
     }
 
-    fn pickle(&self, pickle_key: &[u8]) -> Result<String, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+    #[getter]
+    fn identity_keys(&self) -> PyResult<HashMap<&'static str, String>> {
+        let identity_keys = self.inner()?.identity_keys();
 
-        Ok(self.inner.pickle().encrypt(pickle_key))
+        Ok(HashMap::from([
+            ("ed25519", identity_keys.ed25519.to_base64()),
+            ("curve25519", identity_keys.curve25519.to_base64()),
+        ]))
     }
 
     #[getter]
-    fn ed25519_key(&self) -> String {
-        self.inner.ed25519_key().to_base64()
+    fn ed25519_key(&self) -> PyResult<String> {
+        Ok(self.inner()?.ed25519_key().to_base64())
     }
 
     #[getter]
-    fn curve25519_key(&self) -> String {
-        self.inner.curve25519_key().to_base64()
+    fn curve25519_key(&self) -> PyResult<String> {
+        Ok(self.inner()?.curve25519_key().to_base64())
+    }
+
+    fn sign(&self, message: &str) -> PyResult<String> {
+        Ok(self.inner()?.sign(message).to_base64())
     }
 
-    fn sign(&self, message: &str) -> String {
-        self.inner.sign(message).to_base64()
+    fn sign_raw<'py>(&self, py: Python<'py>, message: &str) -> PyResult<&'py PyBytes> {
+        Ok(PyBytes::new(py, &self.inner()?.sign(message).to_bytes()))
     }
 
+    /// Sign a fixed message and verify it with our own Ed25519 key, a cheap
+    /// consistency check for accounts restored from untrusted storage.
+    fn self_test(&self) -> PyResult<bool> {
+        let inner = self.inner()?;
+        let message = "vodozemac account self-test";
+        let signature = inner.sign(message);
+
+        Ok(inner
+            .ed25519_key()
+            .verify(message.as_bytes(), &signature)
+            .is_ok())
+    }
+
+    /// The one-time keys that haven't been marked as published yet, keys
+    /// disappear from this map once `mark_keys_as_published()` is called.
+    ///
+    /// The map is keyed by the base64 encoded key id, these are the ids used
+    /// for the `signed_curve25519:<key id>` entries of a key upload.
     #[getter]
-    fn one_time_keys(&self) -> HashMap<String, String> {
-        self.inner
+    fn one_time_keys(&self) -> PyResult<HashMap<String, String>> {
+        Ok(self
+            .inner()?
             .one_time_keys()
             .into_iter()
             .map(|(k, v)| (k.to_base64(), v.to_base64()))
-            .collect()
+            .collect())
+    }
+
+    fn signed_one_time_keys<'py>(
+        &self,
+        py: Python<'py>,
+        user_id: &str,
+        device_id: &str,
+    ) -> PyResult<&'py PyDict> {
+        let keys: Vec<_> = {
+            let inner = self.inner()?;
+
+            inner
+                .one_time_keys()
+                .into_iter()
+                .map(|(key_id, key)| {
+                    let key = key.to_base64();
+                    let signature = inner.sign(&format!("{{\"key\":\"{key}\"}}"));
+
+                    (key_id, key, signature)
+                })
+                .collect()
+        };
+
+        let signed_keys = PyDict::new(py);
+
+        for (key_id, key, signature) in keys {
+            let signatures = PyDict::new(py);
+            signatures.set_item(
+                user_id,
+                HashMap::from([(format!("ed25519:{device_id}"), signature.to_base64())]),
+            )?;
+
+            let signed_key = PyDict::new(py);
+            signed_key.set_item("key", key)?;
+            signed_key.set_item("signatures", signatures)?;
+
+            signed_keys.set_item(
+                format!("signed_curve25519:{}", key_id.to_base64()),
+                signed_key,
+            )?;
+        }
+
+        Ok(signed_keys)
     }
 
     #[getter]
-    fn max_number_of_one_time_keys(&self) -> usize {
-        self.inner.max_number_of_one_time_keys()
+    fn max_number_of_one_time_keys(&self) -> PyResult<usize> {
+        Ok(self.inner()?.max_number_of_one_time_keys())
     }
 
-    fn generate_one_time_keys(&mut self, count: usize) {
-        self.inner.generate_one_time_keys(count)
+    /// Generate `count` new one-time keys. Raises a `ValueError` if the
+    /// unpublished keys would no longer fit into `max_number_of_one_time_keys`
+    /// afterwards, vodozemac would otherwise silently drop the oldest ones.
+    fn generate_one_time_keys(&self, py: Python, count: usize) -> PyResult<()> {
+        py.allow_threads(|| {
+            let mut inner = self.inner()?;
+            let max = inner.max_number_of_one_time_keys();
+            let unpublished = inner.one_time_keys().len();
+
+            if count > max.saturating_sub(unpublished) {
+                return Err(PyValueError::new_err(format!(
+                    "Generating {count} one-time keys would leave more than {max} unpublished keys, \
+                     {unpublished} keys are still waiting to be published"
+                )));
+            }
+
+            inner.generate_one_time_keys(count);
+
+            Ok(())
+        })
     }
 
+    /// The fallback key, if one was generated and it hasn't been marked as
+    /// published yet, vodozemac tracks the published state for us.
     #[getter]
-    fn fallback_key(&self) -> HashMap<String, String> {
-        self.inner
+    fn fallback_key(&self) -> PyResult<HashMap<String, String>> {
+        Ok(self
+            .inner()?
             .fallback_key()
             .into_iter()
             .map(|(k, v)| (k.to_base64(), v.to_base64()))
-            .collect()
+            .collect())
+    }
+
+    fn generate_fallback_key(&self) -> PyResult<()> {
+        self.inner()?.generate_fallback_key();
+
+        Ok(())
     }
 
-    fn generate_fallback_key(&mut self) {
-        self.inner.generate_fallback_key()
+    fn forget_fallback_key(&self) -> PyResult<bool> {
+        Ok(self.inner()?.forget_fallback_key())
     }
 
-    fn mark_keys_as_published(&mut self) {
-        self.inner.mark_keys_as_published()
+    fn mark_keys_as_published(&self) -> PyResult<()> {
+        self.inner()?.mark_keys_as_published();
+
+        Ok(())
     }
 
     fn create_outbound_session(
         &self,
-        identity_key: &str,
-        one_time_key: &str,
-    ) -> Result<Session, KeyError> {
-        let identity_key = vodozemac::Curve25519PublicKey::from_base64(identity_key)?;
-        let one_time_key = vodozemac::Curve25519PublicKey::from_base64(one_time_key)?;
+        identity_key: AnyCurve25519PublicKey,
+        one_time_key: AnyCurve25519PublicKey,
+    ) -> PyResult<Session> {
+        let identity_key = identity_key.parse().map_err(KeyError::from)?;
+        let one_time_key = one_time_key.parse().map_err(KeyError::from)?;
 
         let session = self
-            .inner
+            .inner()?
             .create_outbound_session(identity_key, one_time_key);
 
-        Ok(Session { inner: session })
+        Ok(session.into())
     }
 
     fn create_inbound_session(
-        &mut self,
-        identity_key: &str,
+        &self,
+        identity_key: AnyCurve25519PublicKey,
         message: &OlmMessage,
-    ) -> Result<(Session, String), SessionError> {
-        let identity_key = vodozemac::Curve25519PublicKey::from_base64(identity_key)?;
+    ) -> PyResult<(Session, String)> {
+        let (identity_key, message) = parse_inbound_session_input(&identity_key, message)?;
 
-        let message =
-            vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)?;
+        let result = self
+            .inner()?
+            .create_inbound_session(identity_key, &message)
+            .map_err(SessionError::from)?;
 
-        if let vodozemac::olm::OlmMessage::PreKey(message) = message {
-            let result = self.inner.create_inbound_session(identity_key, &message)?;
+        Ok((result.session.into(), result.plaintext))
+    }
 
-            Ok((
-                Session {
-                    inner: result.session,
-                },
-                result.plaintext,
-            ))
-        } else {
-            Err(SessionError::InvalidMessageType)
-        }
+    /// Create inbound sessions for a batch of `(identity_key, message)` pairs
+    /// without reacquiring the GIL for each one. Failures don't abort the
+    /// batch, the returned list contains either a `(Session, plaintext)` tuple
+    /// or the exception for the item at the same position.
+    ///
+    /// The account stays locked for the whole batch, other threads using it
+    /// wait until the batch is done.
+    fn create_inbound_sessions(
+        &self,
+        py: Python,
+        items: Vec<(AnyCurve25519PublicKey, PyRef<OlmMessage>)>,
+    ) -> PyResult<Vec<PyObject>> {
+        let inputs: Vec<_> = items
+            .iter()
+            .map(|(identity_key, message)| parse_inbound_session_input(identity_key, message))
+            .collect();
+
+        let results =
+            py.allow_threads(|| -> Result<Vec<Result<_, SessionError>>, DestroyedError> {
+                let mut inner = self.inner()?;
+
+                Ok(inputs
+                    .into_iter()
+                    .map(|input| {
+                        let (identity_key, message) = input?;
+
+                        Ok(inner.create_inbound_session(identity_key, &message)?)
+                    })
+                    .collect())
+            })?;
+
+        Ok(results
+            .into_iter()
+            .map(|result| match result {
+                Ok(result) => (Session::from(result.session), result.plaintext).into_py(py),
+                Err(e) => PyErr::from(e).into_py(py),
+            })
+            .collect())
+    }
+}
+
+fn parse_inbound_session_input(
+    identity_key: &AnyCurve25519PublicKey,
+    message: &OlmMessage,
+) -> Result<(Curve25519PublicKey, PreKeyMessage), SessionError> {
+    let identity_key = identity_key.parse()?;
+
+    let message =
+        vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)?;
+
+    if let vodozemac::olm::OlmMessage::PreKey(message) = message {
+        Ok((identity_key, message))
+    } else {
+        Err(SessionError::InvalidMessageType)
     }
 }
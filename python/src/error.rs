@@ -37,15 +37,23 @@ macro_rules! create_error {
 create_error!(vodozemac::KeyError, Key);
 create_error!(vodozemac::LibolmPickleError, LibolmPickle);
 create_error!(vodozemac::megolm::SessionKeyDecodeError, SessionKeyDecode);
+create_error!(vodozemac::SignatureError, Signature);
 
 pyo3::create_exception!(module, PickleException, pyo3::exceptions::PyValueError);
+pyo3::create_exception!(module, PickleDecryptionException, PickleException);
+pyo3::create_exception!(module, PickleDecodeException, PickleException);
 pyo3::create_exception!(
     module,
     SessionCreationException,
     pyo3::exceptions::PyValueError
 );
+pyo3::create_exception!(module, MissingOneTimeKeyException, SessionCreationException);
+pyo3::create_exception!(module, PreKeyDecryptionException, SessionCreationException);
 pyo3::create_exception!(module, DecodeException, pyo3::exceptions::PyValueError);
 pyo3::create_exception!(module, SasException, pyo3::exceptions::PyValueError);
+pyo3::create_exception!(module, SasMacMismatchException, SasException);
+pyo3::create_exception!(module, SasUsedException, SasException);
+pyo3::create_exception!(module, SasMacDecodeException, SasException);
 pyo3::create_exception!(
     module,
     OlmDecryptionException,
@@ -56,6 +64,17 @@ pyo3::create_exception!(
     MegolmDecryptionException,
     pyo3::exceptions::PyValueError
 );
+pyo3::create_exception!(module, DestroyedException, pyo3::exceptions::PyValueError);
+
+#[derive(Debug, Error)]
+#[error("The {0} has been destroyed and can't be used anymore")]
+pub struct DestroyedError(pub &'static str);
+
+impl From<DestroyedError> for PyErr {
+    fn from(e: DestroyedError) -> Self {
+        DestroyedException::new_err(e.to_string())
+    }
+}
 
 #[derive(Debug, Error)]
 pub enum MegolmDecryptionError {
@@ -92,9 +111,9 @@ impl From<SasError> for PyErr {
     fn from(e: SasError) -> Self {
         match e {
             SasError::Key(e) => KeyException::new_err(e.to_string()),
-            SasError::Sas(e) => SasException::new_err(e.to_string()),
-            SasError::Mac(e) => SasException::new_err(e.to_string()),
-            SasError::Used => SasException::new_err(e.to_string()),
+            SasError::Sas(e) => SasMacMismatchException::new_err(e.to_string()),
+            SasError::Mac(e) => SasMacDecodeException::new_err(e.to_string()),
+            SasError::Used => SasUsedException::new_err(e.to_string()),
         }
     }
 }
@@ -119,7 +138,15 @@ impl From<SessionError> for PyErr {
             SessionError::Key(e) => KeyException::new_err(e.to_string()),
             SessionError::Decode(e) => DecodeException::new_err(e.to_string()),
             SessionError::Decryption(e) => OlmDecryptionException::new_err(e.to_string()),
-            SessionError::Creation(e) => SessionCreationException::new_err(e.to_string()),
+            SessionError::Creation(e) => match e {
+                vodozemac::olm::SessionCreationError::MissingOneTimeKey(_) => {
+                    MissingOneTimeKeyException::new_err(e.to_string())
+                }
+                vodozemac::olm::SessionCreationError::Decryption(_) => {
+                    PreKeyDecryptionException::new_err(e.to_string())
+                }
+                _ => SessionCreationException::new_err(e.to_string()),
+            },
             SessionError::InvalidMessageType => PyValueError::new_err(e.to_string()),
         }
     }
@@ -127,14 +154,33 @@ impl From<SessionError> for PyErr {
 
 #[derive(Debug, Error)]
 pub enum PickleError {
-    #[error("The pickle key doesn't have the correct size, got {0}, expected 32 bytes")]
+    #[error(
+        "The pickle key doesn't have the correct size, got {0}, expected {} bytes",
+        crate::PICKLE_KEY_LENGTH
+    )]
     InvalidKeySize(usize),
     #[error(transparent)]
     Unpickling(#[from] vodozemac::PickleError),
+    #[error("The pickle dict is missing the {0} field")]
+    MissingField(&'static str),
+    #[error("The {0} field of the pickle dict has an invalid value")]
+    InvalidField(&'static str),
+    #[error("Unsupported pickle dict version {0}")]
+    UnsupportedVersion(u8),
+    #[error("The pickle doesn't belong to the session id of the pickle dict")]
+    SessionIdMismatch,
 }
 
 impl From<PickleError> for PyErr {
     fn from(e: PickleError) -> Self {
-        PickleException::new_err(e.to_string())
+        match e {
+            // The pickle is authenticated, so this is either the wrong key or
+            // a pickle that was tampered with.
+            PickleError::Unpickling(vodozemac::PickleError::Decryption(_)) => {
+                PickleDecryptionException::new_err(e.to_string())
+            }
+            PickleError::Unpickling(_) => PickleDecodeException::new_err(e.to_string()),
+            _ => PickleException::new_err(e.to_string()),
+        }
     }
 }
@@ -1,59 +1,113 @@
-use pyo3::{prelude::*, types::PyType};
+use std::sync::{Mutex, MutexGuard};
+
+use pyo3::{
+    exceptions::{PyIndexError, PyValueError},
+    prelude::*,
+    types::{PyBytes, PyType},
+};
 use vodozemac::megolm::{ExportedSessionKey, MegolmMessage, SessionKey};
 
-use crate::error::{LibolmPickleError, MegolmDecryptionError, PickleError, SessionKeyDecodeError};
+use crate::{
+    convert_pickle_key,
+    error::{LibolmPickleError, MegolmDecryptionError, PickleError, SessionKeyDecodeError},
+};
+
+/// A plaintext given either as `str` or as UTF-8 encoded `bytes`. Decrypted
+/// plaintexts are handed out as `str`, so `bytes` that aren't valid UTF-8
+/// are rejected instead of getting lost on the receiving side.
+pub struct Plaintext<'a>(&'a str);
+
+impl<'a> FromPyObject<'a> for Plaintext<'a> {
+    fn extract(plaintext: &'a PyAny) -> PyResult<Self> {
+        if let Ok(plaintext) = plaintext.downcast::<PyBytes>() {
+            let plaintext = std::str::from_utf8(plaintext.as_bytes()).map_err(|e| {
+                PyValueError::new_err(format!("The plaintext isn't valid UTF-8: {e}"))
+            })?;
+
+            Ok(Self(plaintext))
+        } else {
+            Ok(Self(plaintext.extract()?))
+        }
+    }
+}
+
+impl AsRef<[u8]> for Plaintext<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
 
 #[pyclass]
 pub struct GroupSession {
-    pub(super) inner: vodozemac::megolm::GroupSession,
+    inner: Mutex<vodozemac::megolm::GroupSession>,
+}
+
+impl GroupSession {
+    fn inner(&self) -> MutexGuard<'_, vodozemac::megolm::GroupSession> {
+        self.inner
+            .lock()
+            .expect("The GroupSession lock was poisoned")
+    }
+}
+
+impl From<vodozemac::megolm::GroupSession> for GroupSession {
+    fn from(session: vodozemac::megolm::GroupSession) -> Self {
+        Self {
+            inner: Mutex::new(session),
+        }
+    }
 }
 
 #[pymethods]
 impl GroupSession {
     #[new]
     fn new() -> Self {
-        Self {
-            inner: vodozemac::megolm::GroupSession::new(),
-        }
+        vodozemac::megolm::GroupSession::new().into()
+    }
+
+    fn __repr__(&self) -> String {
+        let inner = self.inner();
+
+        format!(
+            "GroupSession(session_id={}, message_index={})",
+            inner.session_id(),
+            inner.message_index()
+        )
     }
 
     #[getter]
     fn session_id(&self) -> String {
-        self.inner.session_id()
+        self.inner().session_id()
     }
 
     #[getter]
     fn message_index(&self) -> u32 {
-        self.inner.message_index()
+        self.inner().message_index()
     }
 
     #[getter]
     fn session_key(&self) -> String {
-        self.inner.session_key().to_base64()
+        self.inner().session_key().to_base64()
     }
 
-    fn encrypt(&mut self, plaintext: &str) -> String {
-        self.inner.encrypt(plaintext).to_base64()
+    /// Encrypt the given `str` or `bytes`, raises a `ValueError` if the
+    /// `bytes` aren't valid UTF-8.
+    fn encrypt(&self, py: Python, plaintext: Plaintext) -> String {
+        py.allow_threads(|| self.inner().encrypt(plaintext).to_base64())
     }
 
     fn pickle(&self, pickle_key: &[u8]) -> Result<String, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+        let pickle_key = convert_pickle_key(pickle_key)?;
 
-        Ok(self.inner.pickle().encrypt(pickle_key))
+        Ok(self.inner().pickle().encrypt(&pickle_key))
     }
 
     #[classmethod]
     fn from_pickle(_cls: &PyType, pickle: &str, pickle_key: &[u8]) -> Result<Self, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
-        let pickle = vodozemac::megolm::GroupSessionPickle::from_encrypted(pickle, pickle_key)?;
-
-        let session = vodozemac::megolm::GroupSession::from_pickle(pickle);
+        let pickle_key = convert_pickle_key(pickle_key)?;
+        let pickle = vodozemac::megolm::GroupSessionPickle::from_encrypted(pickle, &pickle_key)?;
 
-        Ok(Self { inner: session })
+        Ok(vodozemac::megolm::GroupSession::from_pickle(pickle).into())
     }
 }
 
@@ -65,9 +119,40 @@ pub struct DecryptedMessage {
     message_index: u32,
 }
 
+#[pymethods]
+impl DecryptedMessage {
+    fn __len__(&self) -> usize {
+        2
+    }
+
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<PyObject> {
+        match index {
+            0 | -2 => Ok(self.plaintext.clone().into_py(py)),
+            1 | -1 => Ok(self.message_index.into_py(py)),
+            _ => Err(PyIndexError::new_err("DecryptedMessage index out of range")),
+        }
+    }
+}
+
 #[pyclass]
 pub struct InboundGroupSession {
-    pub(super) inner: vodozemac::megolm::InboundGroupSession,
+    inner: Mutex<vodozemac::megolm::InboundGroupSession>,
+}
+
+impl InboundGroupSession {
+    fn inner(&self) -> MutexGuard<'_, vodozemac::megolm::InboundGroupSession> {
+        self.inner
+            .lock()
+            .expect("The InboundGroupSession lock was poisoned")
+    }
+}
+
+impl From<vodozemac::megolm::InboundGroupSession> for InboundGroupSession {
+    fn from(session: vodozemac::megolm::InboundGroupSession) -> Self {
+        Self {
+            inner: Mutex::new(session),
+        }
+    }
 }
 
 #[pymethods]
@@ -76,37 +161,66 @@ impl InboundGroupSession {
     fn new(session_key: &str) -> Result<Self, SessionKeyDecodeError> {
         let key = SessionKey::from_base64(session_key)?;
 
-        Ok(Self {
-            inner: vodozemac::megolm::InboundGroupSession::new(&key),
-        })
+        Ok(vodozemac::megolm::InboundGroupSession::new(&key).into())
     }
 
     #[classmethod]
     fn import_session(_cls: &PyType, session_key: &str) -> Result<Self, SessionKeyDecodeError> {
         let key = ExportedSessionKey::from_base64(session_key)?;
 
-        Ok(Self {
-            inner: vodozemac::megolm::InboundGroupSession::import(&key),
-        })
+        Ok(vodozemac::megolm::InboundGroupSession::import(&key).into())
+    }
+
+    /// Import a batch of exported session keys, e.g. from a key backup. The
+    /// returned list contains either the session or the exception for the
+    /// key at the same position.
+    #[classmethod]
+    fn import_sessions(cls: &PyType, py: Python, session_keys: Vec<&str>) -> Vec<PyObject> {
+        session_keys
+            .into_iter()
+            .map(|session_key| match Self::import_session(cls, session_key) {
+                Ok(session) => session.into_py(py),
+                Err(e) => PyErr::from(e).into_py(py),
+            })
+            .collect()
     }
 
     #[getter]
     fn session_id(&self) -> String {
-        self.inner.session_id()
+        self.inner().session_id()
     }
 
     #[getter]
     fn first_known_index(&self) -> u32 {
-        self.inner.first_known_index()
+        self.inner().first_known_index()
     }
 
-    fn export_at(&mut self, index: u32) -> Option<String> {
-        self.inner.export_at(index).map(|k| k.to_base64())
+    fn export_at(&self, index: u32) -> Option<String> {
+        self.inner().export_at(index).map(|k| k.to_base64())
     }
 
-    fn decrypt(&mut self, ciphertext: &str) -> Result<DecryptedMessage, MegolmDecryptionError> {
-        let message = MegolmMessage::from_base64(ciphertext)?;
-        let ret = self.inner.decrypt(&message)?;
+    /// Export the session from its first known index, i.e. with everything
+    /// this session is able to decrypt.
+    fn export(&self) -> String {
+        let mut inner = self.inner();
+        let index = inner.first_known_index();
+
+        inner
+            .export_at(index)
+            .expect("A session can always be exported at its first known index")
+            .to_base64()
+    }
+
+    fn decrypt(
+        &self,
+        py: Python,
+        ciphertext: &str,
+    ) -> Result<DecryptedMessage, MegolmDecryptionError> {
+        let ret = py.allow_threads(|| -> Result<_, MegolmDecryptionError> {
+            let message = MegolmMessage::from_base64(ciphertext)?;
+
+            Ok(self.inner().decrypt(&message)?)
+        })?;
 
         Ok(DecryptedMessage {
             plaintext: ret.plaintext,
@@ -115,24 +229,18 @@ impl InboundGroupSession {
     }
 
     fn pickle(&self, pickle_key: &[u8]) -> Result<String, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+        let pickle_key = convert_pickle_key(pickle_key)?;
 
-        Ok(self.inner.pickle().encrypt(pickle_key))
+        Ok(self.inner().pickle().encrypt(&pickle_key))
     }
 
     #[classmethod]
     fn from_pickle(_cls: &PyType, pickle: &str, pickle_key: &[u8]) -> Result<Self, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+        let pickle_key = convert_pickle_key(pickle_key)?;
         let pickle =
-            vodozemac::megolm::InboundGroupSessionPickle::from_encrypted(pickle, pickle_key)?;
-
-        let session = vodozemac::megolm::InboundGroupSession::from_pickle(pickle);
+            vodozemac::megolm::InboundGroupSessionPickle::from_encrypted(pickle, &pickle_key)?;
 
-        Ok(Self { inner: session })
+        Ok(vodozemac::megolm::InboundGroupSession::from_pickle(pickle).into())
     }
 
     #[classmethod]
@@ -141,8 +249,6 @@ impl InboundGroupSession {
         pickle: &str,
         pickle_key: &[u8],
     ) -> Result<Self, LibolmPickleError> {
-        let inner = vodozemac::megolm::InboundGroupSession::from_libolm_pickle(pickle, pickle_key)?;
-
-        Ok(Self { inner })
+        Ok(vodozemac::megolm::InboundGroupSession::from_libolm_pickle(pickle, pickle_key)?.into())
     }
 }
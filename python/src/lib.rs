@@ -3,9 +3,30 @@ mod error;
 mod group_sessions;
 mod sas;
 mod session;
+mod types;
 
 use error::*;
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyType};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+const PICKLE_KEY_LENGTH: usize = 32;
+
+/// Copy the pickle key into a heap allocated buffer that gets zeroized once
+/// we're done with it. The key is copied straight into the buffer, so no
+/// unwiped copies of it are left behind on the stack.
+fn convert_pickle_key(
+    pickle_key: &[u8],
+) -> Result<Box<Zeroizing<[u8; PICKLE_KEY_LENGTH]>>, PickleError> {
+    if pickle_key.len() != PICKLE_KEY_LENGTH {
+        return Err(PickleError::InvalidKeySize(pickle_key.len()));
+    }
+
+    let mut key = Box::new(Zeroizing::new([0u8; PICKLE_KEY_LENGTH]));
+    key.copy_from_slice(pickle_key);
+
+    Ok(key)
+}
 
 #[pyclass]
 pub struct OlmMessage {
@@ -15,26 +36,95 @@ pub struct OlmMessage {
     message_type: usize,
 }
 
+const MESSAGE_TYPE_PRE_KEY: usize = 0;
+const MESSAGE_TYPE_NORMAL: usize = 1;
+
 #[pymethods]
 impl OlmMessage {
     #[new]
-    pub fn new(message_type: usize, ciphertext: &str) -> Self {
-        Self {
+    pub fn new(message_type: usize, ciphertext: &str) -> PyResult<Self> {
+        if message_type != MESSAGE_TYPE_PRE_KEY && message_type != MESSAGE_TYPE_NORMAL {
+            return Err(PyValueError::new_err(format!(
+                "Invalid message type {message_type}, expected {MESSAGE_TYPE_PRE_KEY} \
+                 (pre-key) or {MESSAGE_TYPE_NORMAL} (normal)"
+            )));
+        }
+
+        Ok(Self {
             ciphertext: ciphertext.to_owned(),
             message_type,
-        }
+        })
+    }
+
+    #[classmethod]
+    fn from_parts(
+        _cls: &PyType,
+        message_type: usize,
+        ciphertext: &str,
+    ) -> Result<Self, SessionError> {
+        let message = vodozemac::olm::OlmMessage::from_parts(message_type, ciphertext)?;
+        let (message_type, ciphertext) = message.to_parts();
+
+        Ok(Self {
+            ciphertext,
+            message_type,
+        })
     }
 }
 
+/// Compare two byte strings in constant time, only their lengths leak.
+#[pyfunction]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// The versions of the Olm protocol this module can speak.
+///
+/// vodozemac doesn't expose this, the list is maintained by hand and needs
+/// to be updated once vodozemac learns a new protocol version.
+#[pyfunction]
+fn supported_olm_versions() -> Vec<u8> {
+    vec![1]
+}
+
+/// The versions of the Megolm protocol this module can speak.
+///
+/// vodozemac doesn't expose this, the list is maintained by hand and needs
+/// to be updated once vodozemac learns a new protocol version.
+#[pyfunction]
+fn supported_megolm_versions() -> Vec<u8> {
+    vec![1]
+}
+
+/// Python bindings for vodozemac, a Rust implementation of Olm and Megolm.
+///
+/// The pickle methods copy the `PICKLE_KEY_LENGTH` byte pickle key into a
+/// buffer that is wiped before they return. The `bytes` object passed in is
+/// left untouched, clearing it is up to the caller.
 #[pymodule]
 #[pyo3(name = "vodozemac")]
 fn mymodule(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("__vodozemac_version__", env!("VODOZEMAC_REVISION"))?;
+    m.add_function(wrap_pyfunction!(supported_olm_versions, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_megolm_versions, m)?)?;
+
     m.add_class::<account::Account>()?;
     m.add_class::<session::Session>()?;
     m.add_class::<OlmMessage>()?;
+    m.add("MESSAGE_TYPE_PRE_KEY", MESSAGE_TYPE_PRE_KEY)?;
+    m.add("MESSAGE_TYPE_NORMAL", MESSAGE_TYPE_NORMAL)?;
+    m.add("PICKLE_KEY_LENGTH", PICKLE_KEY_LENGTH)?;
+    m.add_function(wrap_pyfunction!(constant_time_eq, m)?)?;
     m.add_class::<sas::Sas>()?;
+    m.add_class::<sas::EstablishedSas>()?;
+    m.add_class::<sas::SasBytes>()?;
     m.add_class::<group_sessions::GroupSession>()?;
     m.add_class::<group_sessions::InboundGroupSession>()?;
+    m.add_class::<group_sessions::DecryptedMessage>()?;
+    m.add_class::<types::Curve25519PublicKey>()?;
+    m.add_class::<types::Ed25519PublicKey>()?;
+    m.add_class::<types::Ed25519Signature>()?;
 
     m.add("KeyException", py.get_type::<KeyException>())?;
     m.add("DecodeException", py.get_type::<DecodeException>())?;
@@ -46,12 +136,38 @@ fn mymodule(py: Python, m: &PyModule) -> PyResult<()> {
         "SessionKeyDecodeException",
         py.get_type::<SessionKeyDecodeException>(),
     )?;
+    m.add("SignatureException", py.get_type::<SignatureException>())?;
     m.add("PickleException", py.get_type::<PickleException>())?;
+    m.add(
+        "PickleDecryptionException",
+        py.get_type::<PickleDecryptionException>(),
+    )?;
+    m.add(
+        "PickleDecodeException",
+        py.get_type::<PickleDecodeException>(),
+    )?;
     m.add(
         "SessionCreationException",
         py.get_type::<SessionCreationException>(),
     )?;
+    m.add(
+        "MissingOneTimeKeyException",
+        py.get_type::<MissingOneTimeKeyException>(),
+    )?;
+    m.add(
+        "PreKeyDecryptionException",
+        py.get_type::<PreKeyDecryptionException>(),
+    )?;
     m.add("SasException", py.get_type::<SasException>())?;
+    m.add(
+        "SasMacMismatchException",
+        py.get_type::<SasMacMismatchException>(),
+    )?;
+    m.add("SasUsedException", py.get_type::<SasUsedException>())?;
+    m.add(
+        "SasMacDecodeException",
+        py.get_type::<SasMacDecodeException>(),
+    )?;
     m.add(
         "OlmDecryptionException",
         py.get_type::<OlmDecryptionException>(),
@@ -60,6 +176,7 @@ fn mymodule(py: Python, m: &PyModule) -> PyResult<()> {
         "MegolmDecryptionException",
         py.get_type::<MegolmDecryptionException>(),
     )?;
+    m.add("DestroyedException", py.get_type::<DestroyedException>())?;
 
     Ok(())
 }
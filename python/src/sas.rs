@@ -1,4 +1,6 @@
-use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use pyo3::{prelude::*, types::PyBytes};
 use vodozemac::Curve25519PublicKey;
 
 use crate::error::SasError;
@@ -28,8 +30,9 @@ impl Sas {
     }
 
     fn diffie_hellman(&mut self, key: &str) -> Result<EstablishedSas, SasError> {
+        let key = Curve25519PublicKey::from_base64(key)?;
+
         if let Some(sas) = self.inner.take() {
-            let key = Curve25519PublicKey::from_base64(key)?;
             let sas = sas.diffie_hellman(key)?;
 
             Ok(EstablishedSas { inner: sas })
@@ -65,6 +68,39 @@ impl EstablishedSas {
 
         Ok(self.inner.verify_mac(input, info, &tag)?)
     }
+
+    fn calculate_mac_many(
+        &self,
+        inputs: HashMap<String, String>,
+        info: &str,
+    ) -> HashMap<String, String> {
+        inputs
+            .into_iter()
+            .map(|(name, input)| (name, self.calculate_mac(&input, info)))
+            .collect()
+    }
+
+    /// Verify the MAC of every input under the same info, returns the sorted
+    /// names of the inputs whose tag is missing, malformed or doesn't match.
+    fn verify_mac_many(
+        &self,
+        inputs: HashMap<String, String>,
+        info: &str,
+        tags: HashMap<String, String>,
+    ) -> Vec<String> {
+        let mut failed: Vec<String> = inputs
+            .into_iter()
+            .filter(|(name, input)| match tags.get(name) {
+                Some(tag) => self.verify_mac(input, info, tag).is_err(),
+                None => true,
+            })
+            .map(|(name, _)| name)
+            .collect();
+
+        failed.sort();
+
+        failed
+    }
 }
 
 #[pyclass]
@@ -83,4 +119,8 @@ impl SasBytes {
     fn decimals(&self) -> (u16, u16, u16) {
         self.inner.decimals()
     }
+
+    fn as_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, self.inner.as_bytes())
+    }
 }
@@ -1,55 +1,159 @@
-use pyo3::{prelude::*, types::PyType};
+use std::collections::HashMap;
 
-use crate::{LibolmPickleError, PickleError, SessionError};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyType},
+};
+
+use crate::{convert_pickle_key, DestroyedError, LibolmPickleError, PickleError, SessionError};
 
 use super::OlmMessage;
 
+const SESSION_DICT_VERSION: u8 = 1;
+
+fn dict_field<'a, T: FromPyObject<'a>>(
+    data: &'a PyDict,
+    name: &'static str,
+) -> Result<T, PickleError> {
+    data.get_item(name)
+        .ok_or(PickleError::MissingField(name))?
+        .extract()
+        .map_err(|_| PickleError::InvalidField(name))
+}
+
 #[pyclass]
 pub struct Session {
-    pub(super) inner: vodozemac::olm::Session,
+    inner: Option<vodozemac::olm::Session>,
+}
+
+impl Session {
+    fn inner(&self) -> Result<&vodozemac::olm::Session, DestroyedError> {
+        self.inner.as_ref().ok_or(DestroyedError("Session"))
+    }
+
+    fn inner_mut(&mut self) -> Result<&mut vodozemac::olm::Session, DestroyedError> {
+        self.inner.as_mut().ok_or(DestroyedError("Session"))
+    }
+}
+
+impl From<vodozemac::olm::Session> for Session {
+    fn from(session: vodozemac::olm::Session) -> Self {
+        Self {
+            inner: Some(session),
+        }
+    }
 }
 
 #[pymethods]
 impl Session {
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            Some(inner) => format!("Session(session_id={})", inner.session_id()),
+            None => "Session(destroyed)".to_owned(),
+        }
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.destroy();
+
+        false
+    }
+
+    /// Drop the key material of the session, vodozemac zeroizes it on drop.
+    /// Any further use of the session raises a `DestroyedException`.
+    ///
+    /// Calling this is optional, the key material is dropped as well once the
+    /// session gets garbage collected.
+    fn destroy(&mut self) {
+        self.inner = None;
+    }
+
     #[getter]
-    fn session_id(&self) -> String {
-        self.inner.session_id()
+    fn session_id(&self) -> PyResult<String> {
+        Ok(self.inner()?.session_id())
     }
 
-    fn pickle(&self, pickle_key: &[u8]) -> Result<String, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
+    #[getter]
+    fn session_keys(&self) -> PyResult<HashMap<&'static str, String>> {
+        let session_keys = self.inner()?.session_keys();
+
+        Ok(HashMap::from([
+            ("identity_key", session_keys.identity_key.to_base64()),
+            ("base_key", session_keys.base_key.to_base64()),
+            ("one_time_key", session_keys.one_time_key.to_base64()),
+        ]))
+    }
+
+    #[getter]
+    fn has_received_message(&self) -> PyResult<bool> {
+        Ok(self.inner()?.has_received_message())
+    }
+
+    fn pickle(&self, pickle_key: &[u8]) -> PyResult<String> {
+        let pickle_key = convert_pickle_key(pickle_key)?;
 
-        Ok(self.inner.pickle().encrypt(pickle_key))
+        Ok(self.inner()?.pickle().encrypt(&pickle_key))
     }
 
-    fn session_matches(&self, message: &OlmMessage) -> bool {
+    /// Check if the given pre-key message was encrypted using this session,
+    /// normal messages never match since they don't contain the session keys.
+    fn session_matches(&self, message: &OlmMessage) -> PyResult<bool> {
+        let inner = self.inner()?;
         let message =
             vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext);
 
-        match message {
+        Ok(match message {
             Ok(m) => {
                 if let vodozemac::olm::OlmMessage::PreKey(m) = m {
-                    self.inner.session_keys() == m.session_keys()
+                    inner.session_keys() == m.session_keys()
                 } else {
                     false
                 }
             }
             Err(_) => false,
-        }
+        })
     }
 
     #[classmethod]
     fn from_pickle(_cls: &PyType, pickle: &str, pickle_key: &[u8]) -> Result<Self, PickleError> {
-        let pickle_key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| PickleError::InvalidKeySize(pickle_key.len()))?;
-        let pickle = vodozemac::olm::SessionPickle::from_encrypted(pickle, pickle_key)?;
+        let pickle_key = convert_pickle_key(pickle_key)?;
+        let pickle = vodozemac::olm::SessionPickle::from_encrypted(pickle, &pickle_key)?;
+
+        Ok(vodozemac::olm::Session::from_pickle(pickle).into())
+    }
+
+    /// Export the session as a dict holding the encrypted pickle together
+    /// with the session id and a `version` field describing the format.
+    fn to_dict<'py>(&self, py: Python<'py>, pickle_key: &[u8]) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+
+        dict.set_item("version", SESSION_DICT_VERSION)?;
+        dict.set_item("session_id", self.inner()?.session_id())?;
+        dict.set_item("pickle", self.pickle(pickle_key)?)?;
+
+        Ok(dict)
+    }
 
-        let session = vodozemac::olm::Session::from_pickle(pickle);
+    #[classmethod]
+    fn from_dict(cls: &PyType, data: &PyDict, pickle_key: &[u8]) -> PyResult<Self> {
+        let version: u8 = dict_field(data, "version")?;
+
+        if version != SESSION_DICT_VERSION {
+            return Err(PickleError::UnsupportedVersion(version).into());
+        }
 
-        Ok(Self { inner: session })
+        let session_id: &str = dict_field(data, "session_id")?;
+        let session = Self::from_pickle(cls, dict_field(data, "pickle")?, pickle_key)?;
+
+        if session.inner()?.session_id() != session_id {
+            Err(PickleError::SessionIdMismatch.into())
+        } else {
+            Ok(session)
+        }
     }
 
     #[classmethod]
@@ -58,26 +162,40 @@ impl Session {
         pickle: &str,
         pickle_key: &[u8],
     ) -> Result<Self, LibolmPickleError> {
-        let session = vodozemac::olm::Session::from_libolm_pickle(pickle, pickle_key)?;
-
-        Ok(Self { inner: session })
+        Ok(vodozemac::olm::Session::from_libolm_pickle(pickle, pickle_key)?.into())
     }
 
-    fn encrypt(&mut self, plaintext: &str) -> OlmMessage {
-        let message = self.inner.encrypt(plaintext);
+    fn encrypt(&mut self, plaintext: &str) -> PyResult<OlmMessage> {
+        let message = self.inner_mut()?.encrypt(plaintext);
 
         let (message_type, ciphertext) = message.to_parts();
 
-        OlmMessage {
+        Ok(OlmMessage {
             ciphertext,
             message_type,
-        }
+        })
+    }
+
+    fn decrypt(&mut self, message: &OlmMessage) -> PyResult<String> {
+        let inner = self.inner_mut()?;
+        let message =
+            vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)
+                .map_err(SessionError::from)?;
+
+        Ok(inner.decrypt(&message).map_err(SessionError::from)?)
     }
 
-    fn decrypt(&mut self, message: &OlmMessage) -> Result<String, SessionError> {
+    /// Decrypt the message and return the plaintext together with the type of
+    /// the message, either `MESSAGE_TYPE_PRE_KEY` or `MESSAGE_TYPE_NORMAL`.
+    fn decrypt_with_type(&mut self, message: &OlmMessage) -> PyResult<(String, usize)> {
+        let inner = self.inner_mut()?;
         let message =
-            vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)?;
+            vodozemac::olm::OlmMessage::from_parts(message.message_type, &message.ciphertext)
+                .map_err(SessionError::from)?;
+        let (message_type, _) = message.to_parts();
+
+        let plaintext = inner.decrypt(&message).map_err(SessionError::from)?;
 
-        Ok(self.inner.decrypt(&message)?)
+        Ok((plaintext, message_type))
     }
 }
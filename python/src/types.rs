@@ -0,0 +1,166 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use pyo3::{
+    basic::CompareOp,
+    prelude::*,
+    types::{PyBytes, PyType},
+};
+use subtle::ConstantTimeEq;
+
+use crate::error::{KeyError, SignatureError};
+
+fn compare<T: PartialEq>(py: Python, this: &T, other: &T, op: CompareOp) -> PyObject {
+    match op {
+        CompareOp::Eq => (this == other).into_py(py),
+        CompareOp::Ne => (this != other).into_py(py),
+        _ => py.NotImplemented(),
+    }
+}
+
+pub(crate) fn hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Curve25519PublicKey {
+    pub(crate) inner: vodozemac::Curve25519PublicKey,
+}
+
+#[pymethods]
+impl Curve25519PublicKey {
+    #[classmethod]
+    fn from_base64(_cls: &PyType, key: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            inner: vodozemac::Curve25519PublicKey::from_base64(key)?,
+        })
+    }
+
+    fn to_base64(&self) -> String {
+        self.inner.to_base64()
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+        compare(py, self, other, op)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self.inner.as_bytes())
+    }
+}
+
+impl PartialEq for Curve25519PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.as_bytes()[..]
+            .ct_eq(&other.inner.as_bytes()[..])
+            .into()
+    }
+}
+
+impl Eq for Curve25519PublicKey {}
+
+#[derive(FromPyObject)]
+pub enum AnyCurve25519PublicKey<'a> {
+    Key(Curve25519PublicKey),
+    Base64(&'a str),
+}
+
+impl AnyCurve25519PublicKey<'_> {
+    pub fn parse(&self) -> Result<vodozemac::Curve25519PublicKey, vodozemac::KeyError> {
+        match self {
+            AnyCurve25519PublicKey::Key(key) => Ok(key.inner),
+            AnyCurve25519PublicKey::Base64(key) => vodozemac::Curve25519PublicKey::from_base64(key),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Ed25519PublicKey {
+    pub(crate) inner: vodozemac::Ed25519PublicKey,
+}
+
+#[pymethods]
+impl Ed25519PublicKey {
+    #[classmethod]
+    fn from_base64(_cls: &PyType, key: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            inner: vodozemac::Ed25519PublicKey::from_base64(key)?,
+        })
+    }
+
+    fn to_base64(&self) -> String {
+        self.inner.to_base64()
+    }
+
+    fn verify(&self, message: &str, signature: AnyEd25519Signature) -> Result<(), SignatureError> {
+        let signature = signature.parse()?;
+
+        Ok(self.inner.verify(message.as_bytes(), &signature)?)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+        compare(py, self, other, op)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self.inner.as_bytes())
+    }
+}
+
+impl PartialEq for Ed25519PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.as_bytes()[..]
+            .ct_eq(&other.inner.as_bytes()[..])
+            .into()
+    }
+}
+
+impl Eq for Ed25519PublicKey {}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Ed25519Signature {
+    pub(crate) inner: vodozemac::Ed25519Signature,
+}
+
+#[pymethods]
+impl Ed25519Signature {
+    #[classmethod]
+    fn from_base64(_cls: &PyType, signature: &str) -> Result<Self, SignatureError> {
+        Ok(Self {
+            inner: vodozemac::Ed25519Signature::from_base64(signature)?,
+        })
+    }
+
+    fn to_base64(&self) -> String {
+        self.inner.to_base64()
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+}
+
+#[derive(FromPyObject)]
+pub enum AnyEd25519Signature<'a> {
+    Signature(Ed25519Signature),
+    Base64(&'a str),
+}
+
+impl AnyEd25519Signature<'_> {
+    pub fn parse(&self) -> Result<vodozemac::Ed25519Signature, vodozemac::SignatureError> {
+        match self {
+            AnyEd25519Signature::Signature(signature) => Ok(signature.inner),
+            AnyEd25519Signature::Base64(signature) => {
+                vodozemac::Ed25519Signature::from_base64(signature)
+            }
+        }
+    }
+}
@@ -1,7 +1,14 @@
+import base64
+import json
 import vodozemac
 import pytest
 
-from vodozemac import Account, PickleException
+from concurrent.futures import ThreadPoolExecutor
+
+from vodozemac import (Account, DestroyedException, Ed25519PublicKey,
+                       LibolmPickleException, PickleDecodeException,
+                       PickleDecryptionException, PickleException,
+                       PICKLE_KEY_LENGTH)
 
 PICKLE_KEY = b"DEFAULT_PICKLE_KEY_1234567890___"
 
@@ -12,6 +19,19 @@ class TestClass(object):
         assert account.ed25519_key
         assert account.curve25519_key
 
+    def test_repr(self):
+        account = Account()
+
+        assert repr(account) == f"Account(ed25519_key={account.ed25519_key})"
+
+    def test_identity_keys(self):
+        account = Account()
+
+        assert account.identity_keys == {
+            "ed25519": account.ed25519_key,
+            "curve25519": account.curve25519_key,
+        }
+
     def test_generating_onet_time_keys(self):
         account = Account()
 
@@ -20,12 +40,109 @@ class TestClass(object):
         account.generate_one_time_keys(10)
         assert len(account.one_time_keys) == 10
 
+    def test_signed_one_time_keys(self):
+        alice = Account()
+        alice.generate_one_time_keys(5)
+
+        signed_keys = alice.signed_one_time_keys("@alice:example.org", "DEVICEID")
+        assert len(signed_keys) == 5
+
+        ed25519_key = Ed25519PublicKey.from_base64(alice.ed25519_key)
+
+        for key_id, key in alice.one_time_keys.items():
+            signed_key = signed_keys[f"signed_curve25519:{key_id}"]
+            assert signed_key["key"] == key
+
+            signature = signed_key["signatures"]["@alice:example.org"]["ed25519:DEVICEID"]
+            canonical = json.dumps(
+                {"key": key}, sort_keys=True, separators=(",", ":")
+            )
+
+            ed25519_key.verify(canonical, signature)
+
     def test_pickling(self):
         alice = Account()
         pickle = alice.pickle(PICKLE_KEY)
         unpickled = Account.from_pickle(pickle, PICKLE_KEY)
         assert (alice.ed25519_key == unpickled.ed25519_key)
 
+    def test_pickling_preserves_keys(self):
+        alice = Account()
+        alice.generate_one_time_keys(10)
+        alice.generate_fallback_key()
+
+        unpickled = Account.from_pickle(alice.pickle(PICKLE_KEY), PICKLE_KEY)
+
+        assert alice.curve25519_key == unpickled.curve25519_key
+        assert alice.one_time_keys == unpickled.one_time_keys
+        assert alice.fallback_key == unpickled.fallback_key
+
+    def test_sign_raw(self):
+        alice = Account()
+        message = "It's a secret to everybody"
+        signature = alice.sign_raw(message)
+
+        assert isinstance(signature, bytes)
+        assert len(signature) == 64
+        assert base64.b64encode(signature).decode().rstrip("=") == alice.sign(message)
+
+    def test_self_test(self):
+        alice = Account()
+        assert alice.self_test()
+
+        unpickled = Account.from_pickle(alice.pickle(PICKLE_KEY), PICKLE_KEY)
+        assert unpickled.self_test()
+
+    def test_equality(self):
+        alice = Account()
+        unpickled = Account.from_pickle(alice.pickle(PICKLE_KEY), PICKLE_KEY)
+
+        assert alice == unpickled
+        assert not alice != unpickled
+        assert alice != Account()
+
+        unpickled.generate_one_time_keys(1)
+        assert alice == unpickled
+
+    def test_hashing(self):
+        alice = Account()
+        unpickled = Account.from_pickle(alice.pickle(PICKLE_KEY), PICKLE_KEY)
+
+        assert hash(alice) == hash(unpickled)
+        assert len({alice, unpickled, Account()}) == 2
+
+        accounts = {alice: "alice"}
+        assert accounts[unpickled] == "alice"
+
+    def test_destroyed_comparison(self):
+        alice = Account()
+        unpickled = Account.from_pickle(alice.pickle(PICKLE_KEY), PICKLE_KEY)
+        bob = Account()
+        bob.destroy()
+
+        assert bob == bob
+        assert bob != alice
+        assert alice != bob
+        assert bob in {bob, alice}
+        assert bob not in {alice, unpickled}
+
+        accounts = [alice, bob]
+        accounts.remove(bob)
+        assert accounts == [alice]
+
+        unpickled.destroy()
+        assert unpickled != alice
+        hash(unpickled)
+
+    def test_pickle_key_size(self):
+        alice = Account()
+
+        with pytest.raises(PickleException, match="expected 32 bytes"):
+            alice.pickle(b"too short")
+
+        with pytest.raises(PickleException, match="expected 32 bytes"):
+            Account.from_pickle(alice.pickle(PICKLE_KEY), b"too short")
+
     def test_libolm_pickling(self):
         pickle = (
                 "3wpPcPT4xsRYCYF34NcnozxE5bN2E6qwBXQYuoovt/TX//8Dnd8gaKsxN9En/"
@@ -38,13 +155,38 @@ class TestClass(object):
 
         assert unpickled.ed25519_key == "MEQCwaTE/gcrHaxwv06WEVy5xDA30FboFzCAtYhzmoc"
 
+    def test_pickle_key_length_constant(self):
+        assert PICKLE_KEY_LENGTH == len(PICKLE_KEY) == 32
+
+        alice = Account()
+
+        with pytest.raises(PickleException, match=f"expected {PICKLE_KEY_LENGTH} bytes"):
+            alice.pickle(PICKLE_KEY + b"!")
+
+    def test_invalid_libolm_pickle(self):
+        with pytest.raises(LibolmPickleException):
+            Account.from_libolm_pickle("", b"It's a secret to everybody")
+
     def test_invalid_pickle(self):
         with pytest.raises(PickleException):
             Account.from_pickle("", PICKLE_KEY)
 
+    def test_pickle_error_kinds(self):
+        pickle = Account().pickle(PICKLE_KEY)
+
+        with pytest.raises(PickleDecryptionException):
+            Account.from_pickle(pickle, b"ANOTHER_PICKLE_KEY_1234567890___")
+
+        with pytest.raises(PickleDecodeException):
+            Account.from_pickle("not base64!", PICKLE_KEY)
+
+        assert issubclass(PickleDecryptionException, PickleException)
+        assert issubclass(PickleDecodeException, PickleException)
+
     def test_max_one_time_keys(self):
         alice = Account()
         assert isinstance(alice.max_number_of_one_time_keys, int)
+        assert alice.max_number_of_one_time_keys == 50
 
     def test_publish_one_time_keys(self):
         alice = Account()
@@ -54,3 +196,135 @@ class TestClass(object):
 
         alice.mark_keys_as_published()
         assert not alice.one_time_keys
+
+    def test_one_time_key_ids_are_stable(self):
+        alice = Account()
+        alice.generate_one_time_keys(5)
+
+        assert set(alice.one_time_keys) == set(alice.one_time_keys)
+        assert set(alice.one_time_keys) == {
+            key_id.split(":")[1]
+            for key_id in alice.signed_one_time_keys("@alice:example.org", "DEVICEID")
+        }
+
+    def test_one_time_keys_only_contains_unpublished_keys(self):
+        alice = Account()
+        alice.generate_one_time_keys(5)
+        published = alice.one_time_keys
+        alice.mark_keys_as_published()
+
+        alice.generate_one_time_keys(3)
+        unpublished = alice.one_time_keys
+
+        assert len(unpublished) == 3
+        assert not set(unpublished) & set(published)
+
+    def test_fallback_key_only_contains_unpublished_key(self):
+        alice = Account()
+        assert not alice.fallback_key
+
+        alice.generate_fallback_key()
+        assert len(alice.fallback_key) == 1
+
+        alice.mark_keys_as_published()
+        assert not alice.fallback_key
+
+        alice.generate_fallback_key()
+        assert len(alice.fallback_key) == 1
+
+    def test_one_time_key_lifecycle(self):
+        alice = Account()
+
+        alice.generate_one_time_keys(5)
+        first = alice.one_time_keys
+        assert len(first) == 5
+
+        alice.generate_one_time_keys(3)
+        second = alice.one_time_keys
+        assert len(second) == 8
+        assert first.items() <= second.items()
+
+        alice.mark_keys_as_published()
+        assert alice.one_time_keys == {}
+
+        alice.generate_one_time_keys(alice.max_number_of_one_time_keys)
+        assert len(alice.one_time_keys) == alice.max_number_of_one_time_keys
+
+    def test_too_many_one_time_keys(self):
+        alice = Account()
+        max_keys = alice.max_number_of_one_time_keys
+
+        with pytest.raises(ValueError, match=f"more than {max_keys} unpublished keys, 0 keys"):
+            alice.generate_one_time_keys(max_keys + 1)
+
+        assert not alice.one_time_keys
+
+    def test_unpublished_one_time_keys_limit(self):
+        alice = Account()
+        max_keys = alice.max_number_of_one_time_keys
+
+        alice.generate_one_time_keys(max_keys)
+        keys = alice.one_time_keys
+
+        message = (
+            f"Generating 1 one-time keys would leave more than {max_keys} unpublished keys, "
+            f"{max_keys} keys are still waiting to be published"
+        )
+        with pytest.raises(ValueError, match=message):
+            alice.generate_one_time_keys(1)
+
+        assert alice.one_time_keys == keys
+
+        alice.mark_keys_as_published()
+        alice.generate_one_time_keys(1)
+        assert len(alice.one_time_keys) == 1
+
+    def test_threaded_key_generation(self):
+        def generate(_):
+            account = Account()
+            account.generate_one_time_keys(20)
+            return account
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            accounts = list(executor.map(generate, range(8)))
+
+        assert len({account.ed25519_key for account in accounts}) == 8
+        assert all(len(account.one_time_keys) == 20 for account in accounts)
+
+    def test_shared_account_threads(self):
+        account = Account()
+
+        def generate(_):
+            account.generate_one_time_keys(1)
+            keys = account.one_time_keys
+            account.mark_keys_as_published()
+
+            return keys
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            results = list(executor.map(generate, range(32)))
+
+        assert len(results) == 32
+        assert account.one_time_keys == {}
+
+    def test_destroy(self):
+        alice = Account()
+        alice.destroy()
+
+        assert repr(alice) == "Account(destroyed)"
+
+        with pytest.raises(DestroyedException):
+            alice.ed25519_key
+
+        with pytest.raises(DestroyedException):
+            alice.generate_one_time_keys(1)
+
+        with pytest.raises(DestroyedException):
+            alice.pickle(PICKLE_KEY)
+
+    def test_context_manager(self):
+        with Account() as alice:
+            assert alice.ed25519_key
+
+        with pytest.raises(DestroyedException):
+            alice.sign("It's a secret to everybody")
@@ -1,7 +1,10 @@
 import pytest
 
+from concurrent.futures import ThreadPoolExecutor
+
 from vodozemac import (InboundGroupSession, GroupSession, PickleException,
-                       DecodeException, MegolmDecryptionException)
+                       DecodeException, MegolmDecryptionException,
+                       SessionKeyDecodeException, LibolmPickleException)
 
 PICKLE_KEY = b"DEFAULT_PICKLE_KEY_1234567890___"
 
@@ -13,11 +16,34 @@ class TestClass(object):
         session = GroupSession()
         assert isinstance(session.session_id, str)
 
+    def test_session_key(self):
+        session = GroupSession()
+        assert isinstance(session.session_key, str)
+
+        inbound = InboundGroupSession(session.session_key)
+        assert inbound.session_id == session.session_id
+
+        message = inbound.decrypt(session.encrypt("It's a secret to everybody"))
+        assert message.plaintext == "It's a secret to everybody"
+
     def test_session_index(self):
         session = GroupSession()
         assert isinstance(session.message_index, int)
         assert session.message_index == 0
 
+        for i in range(1, 6):
+            session.encrypt("Test")
+            assert session.message_index == i
+
+    def test_session_repr(self):
+        session = GroupSession()
+        session.encrypt("Test")
+
+        assert repr(session) == (
+            f"GroupSession(session_id={session.session_id}, message_index=1)"
+        )
+        assert session.session_key not in repr(session)
+
     def test_outbound_pickle(self):
         session = GroupSession()
         pickle = session.pickle(PICKLE_KEY)
@@ -25,6 +51,19 @@ class TestClass(object):
 
         assert session.session_id == unpickled.session_id
 
+    def test_outbound_pickle_preserves_index(self):
+        session = GroupSession()
+        inbound = InboundGroupSession(session.session_key)
+        session.encrypt("Test 1")
+        session.encrypt("Test 2")
+
+        unpickled = GroupSession.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY)
+        assert unpickled.message_index == 2
+
+        message = inbound.decrypt(unpickled.encrypt("Test 3"))
+        assert message.message_index == 2
+        assert unpickled.message_index == 3
+
     def test_invalid_unpickle(self):
         with pytest.raises(PickleException):
             GroupSession.from_pickle("", PICKLE_KEY)
@@ -32,6 +71,35 @@ class TestClass(object):
         with pytest.raises(PickleException):
             InboundGroupSession.from_pickle("", PICKLE_KEY)
 
+    def test_invalid_libolm_unpickle(self):
+        with pytest.raises(LibolmPickleException):
+            InboundGroupSession.from_libolm_pickle("", b"It's a secret to everybody")
+
+        with pytest.raises(LibolmPickleException):
+            InboundGroupSession.from_libolm_pickle("not base64!", b"It's a secret to everybody")
+
+    def test_libolm_unpickle(self):
+        olm = pytest.importorskip("olm")
+
+        outbound = olm.OutboundGroupSession()
+        early_message = outbound.encrypt("Test 1")
+
+        libolm_session = olm.InboundGroupSession(outbound.session_key)
+        message = outbound.encrypt("Test 2")
+        pickle = libolm_session.pickle("It's a secret to everybody").decode()
+
+        unpickled = InboundGroupSession.from_libolm_pickle(pickle, b"It's a secret to everybody")
+
+        assert unpickled.session_id == libolm_session.id
+        assert unpickled.first_known_index == libolm_session.first_known_index == 1
+
+        decrypted = unpickled.decrypt(message)
+        assert decrypted.plaintext == "Test 2"
+        assert decrypted.message_index == 1
+
+        with pytest.raises(MegolmDecryptionException):
+            unpickled.decrypt(early_message)
+
     def test_inbound_create(self):
         outbound = GroupSession()
         InboundGroupSession(outbound.session_key)
@@ -49,6 +117,26 @@ class TestClass(object):
         pickle = inbound.pickle(PICKLE_KEY)
         InboundGroupSession.from_pickle(pickle, PICKLE_KEY)
 
+    def test_inbound_pickle_preserves_index(self):
+        outbound = GroupSession()
+        outbound.encrypt("Test 1")
+        early_message = outbound.encrypt("Test 2")
+
+        inbound = InboundGroupSession(outbound.session_key)
+        inbound.decrypt(outbound.encrypt("Test 3"))
+
+        unpickled = InboundGroupSession.from_pickle(
+            inbound.pickle(PICKLE_KEY), PICKLE_KEY
+        )
+        assert unpickled.first_known_index == 2
+
+        with pytest.raises(MegolmDecryptionException):
+            unpickled.decrypt(early_message)
+
+        message = unpickled.decrypt(outbound.encrypt("Test 4"))
+        assert message.plaintext == "Test 4"
+        assert message.message_index == 3
+
     def test_inbound_export(self):
         outbound = GroupSession()
         inbound = InboundGroupSession(outbound.session_key)
@@ -59,6 +147,50 @@ class TestClass(object):
         assert message.plaintext == "Test" 
         assert message.message_index == 0
 
+    def test_inbound_export_at(self):
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+        early_message = outbound.encrypt("Test 1")
+        outbound.encrypt("Test 2")
+
+        imported = InboundGroupSession.import_session(inbound.export_at(1))
+
+        with pytest.raises(MegolmDecryptionException):
+            imported.decrypt(early_message)
+
+        message = imported.decrypt(outbound.encrypt("Test 3"))
+        assert message.plaintext == "Test 3"
+        assert message.message_index == 2
+
+        assert imported.export_at(0) is None
+
+    def test_inbound_export_first_known_index(self):
+        outbound = GroupSession()
+        outbound.encrypt("Test 1")
+        early_message = outbound.encrypt("Test 2")
+
+        inbound = InboundGroupSession(outbound.session_key)
+        first_message = outbound.encrypt("Test 3")
+
+        imported = InboundGroupSession.import_session(inbound.export())
+        assert imported.first_known_index == 2
+
+        with pytest.raises(MegolmDecryptionException):
+            imported.decrypt(early_message)
+
+        message = imported.decrypt(first_message)
+        assert message.plaintext == "Test 3"
+        assert message.message_index == 2
+
+    def test_invalid_import(self):
+        with pytest.raises(SessionKeyDecodeException):
+            InboundGroupSession.import_session("")
+
+        outbound = GroupSession()
+
+        with pytest.raises(SessionKeyDecodeException):
+            InboundGroupSession.import_session(outbound.session_key)
+
     def test_first_index(self):
         outbound = GroupSession()
         inbound = InboundGroupSession(outbound.session_key)
@@ -66,12 +198,39 @@ class TestClass(object):
         assert index == 0
         assert isinstance(index, int)
 
+    def test_imported_first_index(self):
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+
+        for _ in range(3):
+            outbound.encrypt("Test")
+
+        imported = InboundGroupSession.import_session(inbound.export_at(3))
+        assert imported.first_known_index == 3
+
     def test_encrypt(self):
         outbound = GroupSession()
         inbound = InboundGroupSession(outbound.session_key)
         message = inbound.decrypt(outbound.encrypt("Test"))
         assert "Test", 0 == inbound.decrypt(outbound.encrypt("Test"))
 
+    def test_encrypt_bytes(self):
+        plaintext = "It's a secret to everybody \u2603\x00"
+
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+
+        message = inbound.decrypt(outbound.encrypt(plaintext.encode()))
+        assert message.plaintext == plaintext
+
+    def test_encrypt_invalid_utf8(self):
+        outbound = GroupSession()
+
+        with pytest.raises(ValueError, match="UTF-8"):
+            outbound.encrypt(b"\xff\xfe\x00")
+
+        assert outbound.message_index == 0
+
     def test_decrypt_twice(self):
         outbound = GroupSession()
         inbound = InboundGroupSession(outbound.session_key)
@@ -81,6 +240,46 @@ class TestClass(object):
         assert message.message_index == 1
         assert message.plaintext == "Test 2"
 
+    def test_decrypt_unpacking(self):
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+
+        plaintext, message_index = inbound.decrypt(outbound.encrypt("Test 1"))
+        assert plaintext == "Test 1"
+        assert message_index == 0
+
+        plaintext, message_index = inbound.decrypt(outbound.encrypt("Test 2"))
+        assert plaintext == "Test 2"
+        assert message_index == 1
+
+    def test_threaded_encryption(self):
+        plaintext = "It's a secret to everybody" * 1000
+
+        def roundtrip(_):
+            outbound = GroupSession()
+            inbound = InboundGroupSession(outbound.session_key)
+
+            return [inbound.decrypt(outbound.encrypt(plaintext)) for _ in range(10)]
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            results = list(executor.map(roundtrip, range(8)))
+
+        for messages in results:
+            assert [m.message_index for m in messages] == list(range(10))
+            assert all(m.plaintext == plaintext for m in messages)
+
+    def test_shared_session_threads(self):
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            messages = list(executor.map(lambda i: outbound.encrypt(f"Test {i}"), range(32)))
+            decrypted = list(executor.map(inbound.decrypt, messages))
+
+        assert outbound.message_index == 32
+        assert sorted(m.message_index for m in decrypted) == list(range(32))
+        assert {m.plaintext for m in decrypted} == {f"Test {i}" for i in range(32)}
+
     def test_decrypt_failure(self):
         outbound = GroupSession()
         inbound = InboundGroupSession(outbound.session_key)
@@ -113,3 +312,17 @@ class TestClass(object):
         outbound = GroupSession()
         inbound = GroupSession(outbound.session_key)
         del inbound
+
+    def test_import_sessions(self):
+        outbound = GroupSession()
+        inbound = InboundGroupSession(outbound.session_key)
+        exported = inbound.export_at(0)
+
+        sessions = InboundGroupSession.import_sessions(["", exported, outbound.session_key])
+
+        assert len(sessions) == 3
+        assert isinstance(sessions[0], SessionKeyDecodeException)
+        assert isinstance(sessions[2], SessionKeyDecodeException)
+
+        assert sessions[1].session_id == outbound.session_id
+        assert sessions[1].decrypt(outbound.encrypt("Test")).plaintext == "Test"
@@ -1,29 +1,79 @@
 import pytest
 
-from vodozemac import SasException, Sas
+from vodozemac import (Curve25519PublicKey, EstablishedSas, KeyException,
+                       SasException, SasMacDecodeException,
+                       SasMacMismatchException, SasUsedException, Sas)
 
 MESSAGE = "Test message"
 EXTRA_INFO = "extra_info"
 
 
 class TestClass(object):
+    def _establish_sas(self):
+        sas_alice = Sas()
+        sas_bob = Sas()
+
+        bob_public_key = sas_bob.public_key
+        sas_bob = sas_bob.diffie_hellman(sas_alice.public_key)
+        sas_alice = sas_alice.diffie_hellman(bob_public_key)
+
+        return sas_alice, sas_bob
+
     def test_sas_creation(self):
         sas = Sas()
         assert sas.public_key
 
+    def test_public_key(self):
+        sas = Sas()
+        public_key = sas.public_key
+
+        assert Curve25519PublicKey.from_base64(public_key).to_base64() == public_key
+        assert sas.public_key == public_key
+        assert Sas().public_key != public_key
+
     def test_other_key_setting(self):
         sas_alice = Sas()
         sas_bob = Sas()
 
         established = sas_alice.diffie_hellman(sas_bob.public_key)
+        assert isinstance(established, EstablishedSas)
 
-    def test_bytes_generating(self):
+    def test_sas_state_machine(self):
         sas_alice = Sas()
         sas_bob = Sas()
 
-        bob_public_key = sas_bob.public_key
-        sas_bob = sas_bob.diffie_hellman(sas_alice.public_key)
-        sas_alice = sas_alice.diffie_hellman(bob_public_key)
+        assert not hasattr(sas_alice, "calculate_mac")
+        assert not hasattr(sas_alice, "bytes")
+
+        sas_alice.diffie_hellman(sas_bob.public_key)
+
+        with pytest.raises(SasException):
+            sas_alice.diffie_hellman(sas_bob.public_key)
+
+    def test_invalid_peer_key(self):
+        sas_alice = Sas()
+        sas_bob = Sas()
+
+        for garbage in ("", "not base64!", "AAAA"):
+            with pytest.raises(KeyException):
+                sas_alice.diffie_hellman(garbage)
+
+        assert isinstance(sas_alice.diffie_hellman(sas_bob.public_key), EstablishedSas)
+
+    def test_reuse_error_message(self):
+        sas_alice = Sas()
+        sas_bob = Sas()
+
+        established = sas_alice.diffie_hellman(sas_bob.public_key)
+
+        for _ in range(2):
+            with pytest.raises(SasException, match="already been used"):
+                sas_alice.diffie_hellman(sas_bob.public_key)
+
+        assert established.calculate_mac(MESSAGE, EXTRA_INFO)
+
+    def test_bytes_generating(self):
+        sas_alice, sas_bob = self._establish_sas()
 
         alice_bytes = sas_alice.bytes(EXTRA_INFO)
         bob_bytes = sas_bob.bytes(EXTRA_INFO)
@@ -31,13 +81,31 @@ class TestClass(object):
         assert alice_bytes.emoji_indices == bob_bytes.emoji_indices
         assert alice_bytes.decimals == bob_bytes.decimals
 
-    def test_mac_generating(self):
+    def test_bytes_shape(self):
         sas_alice = Sas()
         sas_bob = Sas()
 
-        bob_public_key = sas_bob.public_key
-        sas_bob = sas_bob.diffie_hellman(sas_alice.public_key)
-        sas_alice = sas_alice.diffie_hellman(bob_public_key)
+        sas_alice = sas_alice.diffie_hellman(sas_bob.public_key)
+        alice_bytes = sas_alice.bytes(EXTRA_INFO)
+
+        assert len(alice_bytes.emoji_indices) == 7
+        assert all(0 <= index < 64 for index in alice_bytes.emoji_indices)
+
+        assert len(alice_bytes.decimals) == 3
+        assert all(1000 <= decimal <= 9191 for decimal in alice_bytes.decimals)
+
+    def test_raw_bytes(self):
+        sas_alice, sas_bob = self._establish_sas()
+
+        alice_bytes = sas_alice.bytes(EXTRA_INFO).as_bytes()
+
+        assert isinstance(alice_bytes, bytes)
+        assert len(alice_bytes) == 6
+        assert alice_bytes == sas_bob.bytes(EXTRA_INFO).as_bytes()
+        assert alice_bytes != sas_alice.bytes("other_info").as_bytes()
+
+    def test_mac_generating(self):
+        sas_alice, sas_bob = self._establish_sas()
 
         alice_mac = sas_alice.calculate_mac(MESSAGE, EXTRA_INFO)
         bob_mac = sas_bob.calculate_mac(MESSAGE, EXTRA_INFO)
@@ -46,3 +114,74 @@ class TestClass(object):
         sas_bob.verify_mac(MESSAGE, EXTRA_INFO, alice_mac)
 
         assert alice_mac == bob_mac
+
+    def test_mac_verification_failures(self):
+        sas_alice, sas_bob = self._establish_sas()
+
+        bob_mac = sas_bob.calculate_mac(MESSAGE, EXTRA_INFO)
+
+        with pytest.raises(SasException):
+            sas_alice.verify_mac("Another message", EXTRA_INFO, bob_mac)
+
+        with pytest.raises(SasMacDecodeException):
+            sas_alice.verify_mac(MESSAGE, EXTRA_INFO, "not base64!")
+
+        try:
+            sas_alice.verify_mac(MESSAGE, EXTRA_INFO, "not base64!")
+        except SasException:
+            pass
+        else:
+            pytest.fail("A malformed MAC should raise a SasException")
+
+    def test_many_macs(self):
+        sas_alice, sas_bob = self._establish_sas()
+
+        inputs = {
+            "ed25519:DEVICEID": "device key",
+            "ed25519:MASTERKEY": "master key",
+            "ed25519:OTHERKEY": "other key",
+        }
+        tags = sas_bob.calculate_mac_many(inputs, EXTRA_INFO)
+
+        assert tags.keys() == inputs.keys()
+        assert tags["ed25519:DEVICEID"] == sas_bob.calculate_mac("device key", EXTRA_INFO)
+        assert sas_alice.verify_mac_many(inputs, EXTRA_INFO, tags) == []
+
+        tags["ed25519:MASTERKEY"] = sas_bob.calculate_mac("wrong key", EXTRA_INFO)
+        tags["ed25519:DEVICEID"] = "not base64!"
+        del tags["ed25519:OTHERKEY"]
+
+        assert sas_alice.verify_mac_many(inputs, EXTRA_INFO, tags) == [
+            "ed25519:DEVICEID",
+            "ed25519:MASTERKEY",
+            "ed25519:OTHERKEY",
+        ]
+
+    def test_specific_exceptions(self):
+        assert issubclass(SasMacMismatchException, SasException)
+        assert issubclass(SasUsedException, SasException)
+        assert issubclass(SasMacDecodeException, SasException)
+
+        sas_alice = Sas()
+        sas_bob = Sas()
+
+        bob_public_key = sas_bob.public_key
+        sas_bob = sas_bob.diffie_hellman(sas_alice.public_key)
+        established = sas_alice.diffie_hellman(bob_public_key)
+
+        with pytest.raises(SasUsedException):
+            sas_alice.diffie_hellman(bob_public_key)
+
+        bob_mac = sas_bob.calculate_mac(MESSAGE, EXTRA_INFO)
+
+        with pytest.raises(SasMacMismatchException):
+            established.verify_mac("Another message", EXTRA_INFO, bob_mac)
+
+    def test_invalid_base64_mac_generating(self):
+        sas_alice, sas_bob = self._establish_sas()
+
+        alice_mac = sas_alice.calculate_mac_invalid_base64(MESSAGE, EXTRA_INFO)
+        bob_mac = sas_bob.calculate_mac_invalid_base64(MESSAGE, EXTRA_INFO)
+
+        assert alice_mac == bob_mac
+        assert alice_mac != sas_alice.calculate_mac(MESSAGE, EXTRA_INFO)
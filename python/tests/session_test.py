@@ -1,7 +1,15 @@
+import base64
+import random
 import pytest
 
-from vodozemac import (Account, DecodeException, Session, PickleException,
-                       OlmMessage, KeyException)
+from concurrent.futures import ThreadPoolExecutor
+
+from vodozemac import (Account, DecodeException, DestroyedException,
+                       Session, PickleException,
+                       OlmMessage, KeyException, SessionCreationException,
+                       MissingOneTimeKeyException, OlmDecryptionException,
+                       LibolmPickleException, MESSAGE_TYPE_PRE_KEY,
+                       MESSAGE_TYPE_NORMAL)
 
 PICKLE_KEY = b"DEFAULT_PICKLE_KEY_1234567890___"
 
@@ -26,6 +34,35 @@ class TestClass(object):
         assert session_1.session_id != session_2.session_id
         assert isinstance(session_1.session_id, str)
 
+    def test_session_id_shared(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+        assert session.session_id == bob_session.session_id
+
+    def test_session_keys(self):
+        alice = Account()
+        bob = Account()
+        bob.generate_one_time_keys(1)
+        one_time_key = list(bob.one_time_keys.values())[0]
+
+        session = alice.create_outbound_session(bob.curve25519_key, one_time_key)
+        session_keys = session.session_keys
+
+        assert session_keys["identity_key"] == alice.curve25519_key
+        assert session_keys["one_time_key"] == one_time_key
+        assert session_keys["base_key"]
+
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+        assert bob_session.session_keys == session_keys
+
+    def test_session_repr(self):
+        _, _, session = self._create_session()
+
+        assert repr(session) == f"Session(session_id={session.session_id})"
+
     def test_session_clear(self):
         _, _, session = self._create_session()
         del session
@@ -35,10 +72,64 @@ class TestClass(object):
         unpickled = Session.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY)
         assert unpickled.session_id == session.session_id
 
+    def test_session_pickle_continues_ratchet(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+
+        bob_message = bob_session.encrypt("Grumble, Grumble")
+        session = Session.from_pickle(session.pickle(PICKLE_KEY), PICKLE_KEY)
+
+        assert session.decrypt(bob_message) == "Grumble, Grumble"
+
+    def test_session_dict(self):
+        alice, bob, session = self._create_session()
+        exported = session.to_dict(PICKLE_KEY)
+
+        assert exported["version"] == 1
+        assert exported["session_id"] == session.session_id
+
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+
+        restored = Session.from_dict(bob_session.to_dict(PICKLE_KEY), PICKLE_KEY)
+        assert restored.session_id == session.session_id
+        assert restored.decrypt(session.encrypt("Hey! Listen!")) == "Hey! Listen!"
+
+    def test_session_invalid_dict(self):
+        _, _, session = self._create_session()
+        _, _, other = self._create_session()
+        exported = session.to_dict(PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="version"):
+            Session.from_dict({**exported, "version": 2}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="version"):
+            Session.from_dict({**exported, "version": "1"}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="version"):
+            Session.from_dict({**exported, "version": 256}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="pickle"):
+            Session.from_dict({"version": 1, "session_id": session.session_id}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="pickle"):
+            Session.from_dict({**exported, "pickle": b"not a str"}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="session_id"):
+            Session.from_dict({**exported, "session_id": None}, PICKLE_KEY)
+
+        with pytest.raises(PickleException, match="session id"):
+            Session.from_dict({**exported, "session_id": other.session_id}, PICKLE_KEY)
+
     def test_session_invalid_pickle(self):
         with pytest.raises(PickleException):
             Session.from_pickle("", PICKLE_KEY)
 
+    def test_session_invalid_libolm_pickle(self):
+        with pytest.raises(LibolmPickleException):
+            Session.from_libolm_pickle("", b"It's a secret to everybody")
+
     def test_wrong_passphrase_pickle(self):
         alice, bob, session = self._create_session()
         pickle_key = b"It's a secret to everybody 12345"
@@ -64,6 +155,38 @@ class TestClass(object):
         with pytest.raises(DecodeException):
             session.decrypt(message)
 
+    def test_message_types(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        assert message.message_type == MESSAGE_TYPE_PRE_KEY
+
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+        assert bob_session.encrypt("Grumble").message_type == MESSAGE_TYPE_NORMAL
+
+        with pytest.raises(ValueError):
+            OlmMessage(2, message.ciphertext)
+
+    def test_message_from_parts(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+
+        parsed = OlmMessage.from_parts(message.message_type, message.ciphertext)
+        assert parsed.message_type == message.message_type
+        assert parsed.ciphertext == message.ciphertext
+
+        (_, decrypted) = bob.create_inbound_session(alice.curve25519_key, parsed)
+        assert decrypted == "It's a secret to everybody"
+
+    def test_invalid_message_from_parts(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+
+        with pytest.raises(ValueError):
+            OlmMessage.from_parts(2, message.ciphertext)
+
+        with pytest.raises(DecodeException):
+            OlmMessage.from_parts(0, "x")
+
     def test_two_messages(self):
         plaintext = "It's a secret to everybody"
         alice, bob, session = self._create_session()
@@ -77,6 +200,17 @@ class TestClass(object):
 
         assert bob_plaintext == session.decrypt(bob_message)
 
+    def test_has_received_message(self):
+        alice, bob, session = self._create_session()
+        assert session.has_received_message is False
+
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+        assert bob_session.has_received_message is True
+
+        session.decrypt(bob_session.encrypt("Grumble, Grumble"))
+        assert session.has_received_message is True
+
     def test_matches(self):
         plaintext = "It's a secret to everybody"
         alice, bob, session = self._create_session()
@@ -115,3 +249,153 @@ class TestClass(object):
 
         new_message = new_session.encrypt(plaintext)
         assert bob_session.session_matches(new_message) is False
+
+    def test_normal_message_does_not_match(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+
+        session.decrypt(bob_session.encrypt("Grumble, Grumble"))
+        normal_message = session.encrypt("Hey! Listen!")
+
+        assert normal_message.message_type == MESSAGE_TYPE_NORMAL
+        assert bob_session.session_matches(normal_message) is False
+
+    def test_forget_fallback_key(self):
+        plaintext = "It's a secret to everybody"
+        alice = Account()
+        bob = Account()
+
+        assert not bob.forget_fallback_key()
+
+        bob.generate_fallback_key()
+        fallback_key = list(bob.fallback_key.values())[0]
+        bob.generate_fallback_key()
+
+        session = alice.create_outbound_session(bob.curve25519_key, fallback_key)
+        message = session.encrypt(plaintext)
+        (_, decrypted) = bob.create_inbound_session(alice.curve25519_key, message)
+        assert plaintext == decrypted
+
+        assert bob.forget_fallback_key()
+        assert not bob.forget_fallback_key()
+
+        session = alice.create_outbound_session(bob.curve25519_key, fallback_key)
+        message = session.encrypt(plaintext)
+
+        with pytest.raises(SessionCreationException):
+            bob.create_inbound_session(alice.curve25519_key, message)
+
+    def test_session_destroy(self):
+        _, _, session = self._create_session()
+        session.destroy()
+
+        assert repr(session) == "Session(destroyed)"
+
+        with pytest.raises(DestroyedException):
+            session.encrypt("It's a secret to everybody")
+
+    def test_session_context_manager(self):
+        alice, bob, session = self._create_session()
+
+        with session:
+            message = session.encrypt("It's a secret to everybody")
+
+        with pytest.raises(DestroyedException):
+            session.session_id
+
+        with bob.create_inbound_session(alice.curve25519_key, message)[0] as bob_session:
+            assert bob_session.has_received_message
+
+    def test_create_inbound_sessions(self):
+        alice, bob, session = self._create_session()
+        carol = Account()
+        bob.mark_keys_as_published()
+        bob.generate_one_time_keys(1)
+        one_time_key = list(bob.one_time_keys.values())[0]
+        carol_session = carol.create_outbound_session(bob.curve25519_key, one_time_key)
+
+        results = bob.create_inbound_sessions([
+            (alice.curve25519_key, session.encrypt("It's a secret to everybody")),
+            (carol.curve25519_key, OlmMessage(MESSAGE_TYPE_NORMAL, "")),
+            (carol.curve25519_key, carol_session.encrypt("Grumble, Grumble")),
+        ])
+
+        assert len(results) == 3
+
+        (alice_session, plaintext) = results[0]
+        assert alice_session.session_id == session.session_id
+        assert plaintext == "It's a secret to everybody"
+
+        assert isinstance(results[1], ValueError)
+
+        (carol_inbound, plaintext) = results[2]
+        assert carol_inbound.session_id == carol_session.session_id
+        assert plaintext == "Grumble, Grumble"
+
+    def test_create_inbound_sessions_shared_account(self):
+        bob = Account()
+        bob.generate_one_time_keys(16)
+        one_time_keys = list(bob.one_time_keys.values())
+        bob.mark_keys_as_published()
+
+        items = []
+
+        for one_time_key in one_time_keys:
+            alice = Account()
+            session = alice.create_outbound_session(bob.curve25519_key, one_time_key)
+            items.append((alice.curve25519_key, session.encrypt("Test")))
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            batches = [
+                executor.submit(bob.create_inbound_sessions, items[i:i + 4])
+                for i in range(0, len(items), 4)
+            ]
+            keys = [executor.submit(lambda: bob.identity_keys) for _ in range(8)]
+
+            results = [result for batch in batches for result in batch.result()]
+            assert all(key.result() == bob.identity_keys for key in keys)
+
+        assert len(results) == 16
+        assert all(plaintext == "Test" for (_, plaintext) in results)
+
+    def test_decrypt_with_type(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+
+        message = session.encrypt("Hey! Listen!")
+        assert bob_session.decrypt_with_type(message) == ("Hey! Listen!", MESSAGE_TYPE_PRE_KEY)
+
+        session.decrypt(bob_session.encrypt("Grumble, Grumble"))
+
+        message = session.encrypt("Hey! Listen!")
+        assert bob_session.decrypt_with_type(message) == ("Hey! Listen!", MESSAGE_TYPE_NORMAL)
+
+    def test_reused_one_time_key(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        bob.create_inbound_session(alice.curve25519_key, message)
+
+        with pytest.raises(MissingOneTimeKeyException):
+            bob.create_inbound_session(alice.curve25519_key, message)
+
+        assert issubclass(MissingOneTimeKeyException, SessionCreationException)
+
+    def test_decrypt_random_input(self):
+        alice, bob, session = self._create_session()
+        message = session.encrypt("It's a secret to everybody")
+        (bob_session, _) = bob.create_inbound_session(alice.curve25519_key, message)
+
+        rng = random.Random(0)
+
+        for _ in range(500):
+            garbage = bytes(rng.getrandbits(8) for _ in range(rng.randrange(256)))
+            ciphertext = base64.b64encode(garbage).decode().rstrip("=")
+
+            for message_type in (MESSAGE_TYPE_PRE_KEY, MESSAGE_TYPE_NORMAL):
+                with pytest.raises((DecodeException, OlmDecryptionException)):
+                    bob_session.decrypt(OlmMessage(message_type, ciphertext))
+
+        with pytest.raises(DecodeException):
+            bob_session.decrypt(OlmMessage(MESSAGE_TYPE_NORMAL, "not base64!"))
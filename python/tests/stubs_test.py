@@ -0,0 +1,21 @@
+import ast
+import pathlib
+import vodozemac
+
+STUB = pathlib.Path(__file__).parent.parent / "vodozemac.pyi"
+
+class TestClass(object):
+    def test_stub_matches_module(self):
+        tree = ast.parse(STUB.read_text())
+
+        for node in tree.body:
+            if isinstance(node, ast.ClassDef):
+                cls = getattr(vodozemac, node.name)
+
+                for member in node.body:
+                    if isinstance(member, ast.FunctionDef):
+                        assert hasattr(cls, member.name), f"{node.name}.{member.name}"
+            elif isinstance(node, ast.FunctionDef):
+                assert hasattr(vodozemac, node.name)
+            elif isinstance(node, ast.AnnAssign):
+                assert hasattr(vodozemac, node.target.id)
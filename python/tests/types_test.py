@@ -0,0 +1,123 @@
+import pytest
+
+from vodozemac import (Account, Curve25519PublicKey, Ed25519PublicKey,
+                       Ed25519Signature, KeyException, SignatureException,
+                       constant_time_eq)
+
+MESSAGE = "It's a secret to everybody"
+
+
+class TestClass(object):
+    def test_curve25519_key_parsing(self):
+        account = Account()
+        key = Curve25519PublicKey.from_base64(account.curve25519_key)
+
+        assert key.to_base64() == account.curve25519_key
+
+    def test_invalid_curve25519_key(self):
+        with pytest.raises(KeyException):
+            Curve25519PublicKey.from_base64("")
+
+    def test_session_from_curve25519_keys(self):
+        alice = Account()
+        bob = Account()
+        bob.generate_one_time_keys(1)
+
+        identity_key = Curve25519PublicKey.from_base64(bob.curve25519_key)
+        one_time_key = Curve25519PublicKey.from_base64(
+            list(bob.one_time_keys.values())[0]
+        )
+
+        session = alice.create_outbound_session(identity_key, one_time_key)
+        message = session.encrypt(MESSAGE)
+
+        alice_key = Curve25519PublicKey.from_base64(alice.curve25519_key)
+        (_, plaintext) = bob.create_inbound_session(alice_key, message)
+
+        assert plaintext == MESSAGE
+
+    def test_ed25519_key_parsing(self):
+        account = Account()
+        key = Ed25519PublicKey.from_base64(account.ed25519_key)
+
+        assert key.to_base64() == account.ed25519_key
+
+    def test_invalid_ed25519_key(self):
+        with pytest.raises(KeyException):
+            Ed25519PublicKey.from_base64("")
+
+    def test_signature_verification(self):
+        account = Account()
+        key = Ed25519PublicKey.from_base64(account.ed25519_key)
+        signature = account.sign(MESSAGE)
+
+        key.verify(MESSAGE, signature)
+
+    def test_tampered_message(self):
+        account = Account()
+        key = Ed25519PublicKey.from_base64(account.ed25519_key)
+        signature = account.sign(MESSAGE)
+
+        with pytest.raises(SignatureException):
+            key.verify("It's a secret to nobody", signature)
+
+    def test_invalid_signature(self):
+        account = Account()
+        key = Ed25519PublicKey.from_base64(account.ed25519_key)
+
+        with pytest.raises(SignatureException):
+            key.verify(MESSAGE, "")
+
+    def test_signature_parsing(self):
+        account = Account()
+        signature = Ed25519Signature.from_base64(account.sign(MESSAGE))
+
+        assert signature.to_base64() == account.sign(MESSAGE)
+        assert signature.to_bytes() == account.sign_raw(MESSAGE)
+
+    def test_invalid_signature_parsing(self):
+        with pytest.raises(SignatureException):
+            Ed25519Signature.from_base64("")
+
+    def test_signature_object_verification(self):
+        account = Account()
+        key = Ed25519PublicKey.from_base64(account.ed25519_key)
+        signature = Ed25519Signature.from_base64(account.sign(MESSAGE))
+
+        key.verify(MESSAGE, signature)
+
+        with pytest.raises(SignatureException):
+            key.verify("It's a secret to nobody", signature)
+
+    def test_key_equality(self):
+        alice = Account()
+        bob = Account()
+
+        alice_key = Curve25519PublicKey.from_base64(alice.curve25519_key)
+        assert alice_key == Curve25519PublicKey.from_base64(alice.curve25519_key)
+        assert alice_key != Curve25519PublicKey.from_base64(bob.curve25519_key)
+
+        alice_key = Ed25519PublicKey.from_base64(alice.ed25519_key)
+        assert alice_key == Ed25519PublicKey.from_base64(alice.ed25519_key)
+        assert alice_key != Ed25519PublicKey.from_base64(bob.ed25519_key)
+
+    def test_key_hashing(self):
+        account = Account()
+
+        curve_keys = {
+            Curve25519PublicKey.from_base64(account.curve25519_key),
+            Curve25519PublicKey.from_base64(account.curve25519_key),
+        }
+        assert len(curve_keys) == 1
+
+        ed_keys = {
+            Ed25519PublicKey.from_base64(account.ed25519_key),
+            Ed25519PublicKey.from_base64(account.ed25519_key),
+        }
+        assert len(ed_keys) == 1
+
+    def test_constant_time_eq(self):
+        assert constant_time_eq(b"It's a secret", b"It's a secret")
+        assert not constant_time_eq(b"It's a secret", b"It's a secreT")
+        assert not constant_time_eq(b"It's a secret", b"It's a secret to everybody")
+        assert constant_time_eq(b"", b"")
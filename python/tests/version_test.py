@@ -0,0 +1,12 @@
+import re
+import vodozemac
+
+
+class TestClass(object):
+    def test_versions(self):
+        assert re.fullmatch(r"\d+\.\d+\.\d+", vodozemac.__version__)
+        assert re.fullmatch(r"[0-9a-f]{40}", vodozemac.__vodozemac_version__)
+
+    def test_supported_protocol_versions(self):
+        assert vodozemac.supported_olm_versions() == [1]
+        assert vodozemac.supported_megolm_versions() == [1]
@@ -0,0 +1,224 @@
+"""Python bindings for vodozemac, a Rust implementation of Olm and Megolm.
+
+The pickle methods copy the `PICKLE_KEY_LENGTH` byte pickle key into a
+buffer that is wiped before they return. The `bytes` object passed in is
+left untouched, clearing it is up to the caller.
+"""
+
+from types import TracebackType
+from typing import Literal, Optional, Type, Union
+
+__version__: str
+__vodozemac_version__: str
+
+MESSAGE_TYPE_PRE_KEY: Literal[0]
+MESSAGE_TYPE_NORMAL: Literal[1]
+PICKLE_KEY_LENGTH: Literal[32]
+
+def constant_time_eq(a: bytes, b: bytes) -> bool: ...
+def supported_olm_versions() -> list[int]: ...
+def supported_megolm_versions() -> list[int]: ...
+
+class KeyException(ValueError): ...
+class DecodeException(ValueError): ...
+class LibolmPickleException(ValueError): ...
+class SessionKeyDecodeException(ValueError): ...
+class SignatureException(ValueError): ...
+class PickleException(ValueError): ...
+class PickleDecryptionException(PickleException): ...
+class PickleDecodeException(PickleException): ...
+class SessionCreationException(ValueError): ...
+class MissingOneTimeKeyException(SessionCreationException): ...
+class PreKeyDecryptionException(SessionCreationException): ...
+class SasException(ValueError): ...
+class SasMacMismatchException(SasException): ...
+class SasUsedException(SasException): ...
+class SasMacDecodeException(SasException): ...
+class OlmDecryptionException(ValueError): ...
+class MegolmDecryptionException(ValueError): ...
+class DestroyedException(ValueError): ...
+
+class Curve25519PublicKey:
+    @classmethod
+    def from_base64(cls, key: str) -> Curve25519PublicKey: ...
+    def to_base64(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class Ed25519PublicKey:
+    @classmethod
+    def from_base64(cls, key: str) -> Ed25519PublicKey: ...
+    def to_base64(self) -> str: ...
+    def verify(
+        self, message: str, signature: Union[Ed25519Signature, str]
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class Ed25519Signature:
+    @classmethod
+    def from_base64(cls, signature: str) -> Ed25519Signature: ...
+    def to_base64(self) -> str: ...
+    def to_bytes(self) -> bytes: ...
+
+class OlmMessage:
+    @property
+    def ciphertext(self) -> str: ...
+    @property
+    def message_type(self) -> int: ...
+    def __init__(self, message_type: int, ciphertext: str) -> None: ...
+    @classmethod
+    def from_parts(cls, message_type: int, ciphertext: str) -> OlmMessage: ...
+
+class Account:
+    def __init__(self) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    def __enter__(self) -> Account: ...
+    def __exit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_value: Optional[BaseException],
+        traceback: Optional[TracebackType],
+    ) -> bool: ...
+    def destroy(self) -> None: ...
+    @classmethod
+    def from_pickle(cls, pickle: str, pickle_key: bytes) -> Account: ...
+    @classmethod
+    def from_libolm_pickle(cls, pickle: str, pickle_key: bytes) -> Account: ...
+    def pickle(self, pickle_key: bytes) -> str: ...
+    @property
+    def identity_keys(self) -> dict[str, str]: ...
+    @property
+    def ed25519_key(self) -> str: ...
+    @property
+    def curve25519_key(self) -> str: ...
+    def sign(self, message: str) -> str: ...
+    def sign_raw(self, message: str) -> bytes: ...
+    def self_test(self) -> bool: ...
+    @property
+    def one_time_keys(self) -> dict[str, str]: ...
+    def signed_one_time_keys(
+        self, user_id: str, device_id: str
+    ) -> dict[str, dict[str, object]]: ...
+    @property
+    def max_number_of_one_time_keys(self) -> int: ...
+    def generate_one_time_keys(self, count: int) -> None: ...
+    @property
+    def fallback_key(self) -> dict[str, str]: ...
+    def generate_fallback_key(self) -> None: ...
+    def forget_fallback_key(self) -> bool: ...
+    def mark_keys_as_published(self) -> None: ...
+    def create_outbound_session(
+        self,
+        identity_key: Union[Curve25519PublicKey, str],
+        one_time_key: Union[Curve25519PublicKey, str],
+    ) -> Session: ...
+    def create_inbound_session(
+        self, identity_key: Union[Curve25519PublicKey, str], message: OlmMessage
+    ) -> tuple[Session, str]: ...
+    def create_inbound_sessions(
+        self, items: list[tuple[Union[Curve25519PublicKey, str], OlmMessage]]
+    ) -> list[Union[tuple[Session, str], ValueError]]: ...
+
+class Session:
+    def __enter__(self) -> Session: ...
+    def __exit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_value: Optional[BaseException],
+        traceback: Optional[TracebackType],
+    ) -> bool: ...
+    def destroy(self) -> None: ...
+    @property
+    def session_id(self) -> str: ...
+    @property
+    def session_keys(self) -> dict[str, str]: ...
+    @property
+    def has_received_message(self) -> bool: ...
+    def pickle(self, pickle_key: bytes) -> str: ...
+    def session_matches(self, message: OlmMessage) -> bool: ...
+    @classmethod
+    def from_pickle(cls, pickle: str, pickle_key: bytes) -> Session: ...
+    @classmethod
+    def from_libolm_pickle(cls, pickle: str, pickle_key: bytes) -> Session: ...
+    def to_dict(self, pickle_key: bytes) -> dict[str, Union[int, str]]: ...
+    @classmethod
+    def from_dict(
+        cls, data: dict[str, Union[int, str]], pickle_key: bytes
+    ) -> Session: ...
+    def encrypt(self, plaintext: str) -> OlmMessage: ...
+    def decrypt(self, message: OlmMessage) -> str: ...
+    def decrypt_with_type(self, message: OlmMessage) -> tuple[str, int]: ...
+
+class Sas:
+    def __init__(self) -> None: ...
+    @property
+    def public_key(self) -> str: ...
+    def diffie_hellman(self, key: str) -> EstablishedSas: ...
+
+class EstablishedSas:
+    def bytes(self, info: str) -> SasBytes: ...
+    def calculate_mac_invalid_base64(self, input: str, info: str) -> str: ...
+    def calculate_mac(self, input: str, info: str) -> str: ...
+    def verify_mac(self, input: str, info: str, tag: str) -> None: ...
+    def calculate_mac_many(
+        self, inputs: dict[str, str], info: str
+    ) -> dict[str, str]: ...
+    def verify_mac_many(
+        self, inputs: dict[str, str], info: str, tags: dict[str, str]
+    ) -> list[str]: ...
+
+class SasBytes:
+    @property
+    def emoji_indices(self) -> list[int]: ...
+    @property
+    def decimals(self) -> tuple[int, int, int]: ...
+    def as_bytes(self) -> bytes: ...
+
+class GroupSession:
+    def __init__(self) -> None: ...
+    @property
+    def session_id(self) -> str: ...
+    @property
+    def message_index(self) -> int: ...
+    @property
+    def session_key(self) -> str: ...
+    def encrypt(self, plaintext: Union[bytes, str]) -> str: ...
+    def pickle(self, pickle_key: bytes) -> str: ...
+    @classmethod
+    def from_pickle(cls, pickle: str, pickle_key: bytes) -> GroupSession: ...
+
+class DecryptedMessage:
+    @property
+    def plaintext(self) -> str: ...
+    @property
+    def message_index(self) -> int: ...
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> Union[str, int]: ...
+
+class InboundGroupSession:
+    def __init__(self, session_key: str) -> None: ...
+    @classmethod
+    def import_session(cls, session_key: str) -> InboundGroupSession: ...
+    @classmethod
+    def import_sessions(
+        cls, session_keys: list[str]
+    ) -> list[Union[InboundGroupSession, SessionKeyDecodeException]]: ...
+    @property
+    def session_id(self) -> str: ...
+    @property
+    def first_known_index(self) -> int: ...
+    def export_at(self, index: int) -> Optional[str]: ...
+    def export(self) -> str: ...
+    def decrypt(self, ciphertext: str) -> DecryptedMessage: ...
+    def pickle(self, pickle_key: bytes) -> str: ...
+    @classmethod
+    def from_pickle(cls, pickle: str, pickle_key: bytes) -> InboundGroupSession: ...
+    @classmethod
+    def from_libolm_pickle(
+        cls, pickle: str, pickle_key: bytes
+    ) -> InboundGroupSession: ...